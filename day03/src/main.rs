@@ -1,5 +1,7 @@
+use itertools::Itertools;
 use std::collections::hash_map::RandomState;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
 
 struct Rucksack(Vec<char>);
 
@@ -8,43 +10,513 @@ impl Rucksack {
         Self(input.chars().collect())
     }
 
-    fn odd_one(&self) -> char {
+    fn has_odd_length(&self) -> bool {
+        self.0.len() % 2 == 1
+    }
+
+    fn compartment_intersection(&self) -> Vec<char> {
         let (left, right) = self.0.split_at(self.0.len() / 2);
 
         let set1: HashSet<&char, RandomState> = HashSet::from_iter(left.iter());
         let set2: HashSet<&char, RandomState> = HashSet::from_iter(right.iter());
-        let intersection = set1.intersection(&set2).collect::<Vec<&&char>>();
-        let [odd_one] = intersection[..] else { panic!("there should only be one intersecion") };
-        **odd_one
+        set1.intersection(&set2).map(|c| **c).sorted().collect()
+    }
+
+    fn odd_one(&self) -> Result<char, IntersectionError> {
+        IntersectionError::single(self.compartment_intersection())
     }
 
-    fn common_one(a: &Self, b: &Self, c: &Self) -> char {
-        let set1: HashSet<char, RandomState> = HashSet::from_iter(a.0.clone().into_iter());
-        let set2 = HashSet::from_iter(b.0.clone().into_iter());
+    fn common_items(a: &Self, b: &Self, c: &Self) -> Vec<char> {
+        let set1: HashSet<char, RandomState> = HashSet::from_iter(a.0.clone());
+        let set2 = HashSet::from_iter(b.0.clone());
         let set3 = HashSet::from_iter(c.0.iter());
 
         let intermediary_set = set1.intersection(&set2).collect::<HashSet<&char>>();
-        let intersection = set3.intersection(&intermediary_set).collect::<Vec<_>>();
+        set3.intersection(&intermediary_set)
+            .map(|c| **c)
+            .sorted()
+            .collect()
+    }
+
+    fn common_one(a: &Self, b: &Self, c: &Self) -> Result<char, IntersectionError> {
+        IntersectionError::single(Self::common_items(a, b, c))
+    }
+
+    /// Finds the fewest swaps between the compartments after which no item type is in both of them.
+    /// Returns `None` if the item counts do not allow such an arrangement.
+    fn rearrangement(&self) -> Option<Vec<Swap>> {
+        let half = self.0.len() / 2;
+        let (left, right) = self.0.split_at(half);
+        let types = self.0.iter().copied().unique().sorted().collect::<Vec<_>>();
+        let counts = types
+            .iter()
+            .map(|item| {
+                (
+                    left.iter().filter(|c| *c == item).count(),
+                    right.iter().filter(|c| *c == item).count(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // best[i][size] is the fewest items which have to be moved to the left compartment,
+        // if the types kept on the left among the first i types fill `size` places
+        let mut best = vec![vec![None; half + 1]; types.len() + 1];
+        best[0][0] = Some(0);
+        for (i, &(l, r)) in counts.iter().enumerate() {
+            for size in 0..=half {
+                let Some(moved) = best[i][size] else { continue };
+                relax(&mut best[i + 1][size], moved);
+                if size + l + r <= half {
+                    relax(&mut best[i + 1][size + l + r], moved + r);
+                }
+            }
+        }
+
+        let mut size = half;
+        let mut moved = best[types.len()][half]?;
+        let mut left_types = HashSet::new();
+        for i in (0..types.len()).rev() {
+            let (l, r) = counts[i];
+            if size >= l + r && moved >= r && best[i][size - l - r] == Some(moved - r) {
+                left_types.insert(types[i]);
+                size -= l + r;
+                moved -= r;
+            }
+        }
+
+        let to_right = (0..half).filter(|&i| !left_types.contains(&self.0[i]));
+        let to_left = (half..self.0.len()).filter(|&i| left_types.contains(&self.0[i]));
+        Some(
+            to_right
+                .zip_eq(to_left)
+                .map(|(left, right)| Swap { left, right })
+                .collect(),
+        )
+    }
+}
+
+fn relax(best: &mut Option<usize>, candidate: usize) {
+    if best.is_none_or(|best| candidate < best) {
+        *best = Some(candidate);
+    }
+}
+
+/// Exchanges the items at the two positions of a rucksack, `left` lies in the first compartment.
+#[derive(Debug, PartialEq, Clone, Copy)]
+struct Swap {
+    left: usize,
+    right: usize,
+}
+
+#[derive(Debug, PartialEq)]
+enum IntersectionError {
+    Missing,
+    Ambiguous(Vec<char>),
+}
+
+impl IntersectionError {
+    fn single(items: Vec<char>) -> Result<char, Self> {
+        match items[..] {
+            [] => Err(IntersectionError::Missing),
+            [single] => Ok(single),
+            _ => Err(IntersectionError::Ambiguous(items)),
+        }
+    }
+}
+
+impl std::fmt::Display for IntersectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntersectionError::Missing => write!(f, "no common item"),
+            IntersectionError::Ambiguous(items) => write!(
+                f,
+                "{} common items ({})",
+                items.len(),
+                items.iter().collect::<String>()
+            ),
+        }
+    }
+}
+
+/// A line of the input which does not have exactly one item in both compartments,
+/// whose compartments cannot be split evenly or which contains items without priority. `line` is 1-based.
+#[derive(Debug, PartialEq)]
+struct RucksackReport {
+    line: usize,
+    items: Vec<char>,
+    odd_length: bool,
+    unknown_items: Vec<char>,
+}
+
+/// A group of three lines which does not share exactly one badge. `first_line` is 1-based.
+#[derive(Debug, PartialEq)]
+struct GroupReport {
+    first_line: usize,
+    size: usize,
+    items: Vec<char>,
+}
+
+fn validate_rucksacks(input: &str, table: &PriorityTable) -> Vec<RucksackReport> {
+    input
+        .lines()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            let rucksack = Rucksack::from_str(line);
+            let items = rucksack.compartment_intersection();
+            let odd_length = rucksack.has_odd_length();
+            let unknown_items = table.unknown_items(&rucksack);
+            if items.len() == 1 && !odd_length && unknown_items.is_empty() {
+                return None;
+            }
+            Some(RucksackReport {
+                line: index + 1,
+                items,
+                odd_length,
+                unknown_items,
+            })
+        })
+        .collect()
+}
+
+fn validate_groups(input: &str) -> Vec<GroupReport> {
+    let lines = input.lines().collect::<Vec<_>>();
+    lines
+        .chunks(3)
+        .enumerate()
+        .filter_map(|(index, chunk)| {
+            let items = match chunk {
+                [a, b, c] => Rucksack::common_items(
+                    &Rucksack::from_str(a),
+                    &Rucksack::from_str(b),
+                    &Rucksack::from_str(c),
+                ),
+                _ => vec![],
+            };
+            if chunk.len() == 3 && items.len() == 1 {
+                return None;
+            }
+            Some(GroupReport {
+                first_line: index * 3 + 1,
+                size: chunk.len(),
+                items,
+            })
+        })
+        .collect()
+}
+
+fn print_validation(input: &str, table: &PriorityTable) {
+    let rucksacks = validate_rucksacks(input, table);
+    for report in &rucksacks {
+        let problem = match IntersectionError::single(report.items.clone()) {
+            Ok(_) => "one common item".to_string(),
+            Err(error) => error.to_string(),
+        };
+        println!(
+            "line {}: {}{}{}",
+            report.line,
+            problem,
+            if report.odd_length {
+                ", compartments have odd length"
+            } else {
+                ""
+            },
+            if report.unknown_items.is_empty() {
+                "".to_string()
+            } else {
+                format!(
+                    ", unknown items ({})",
+                    report.unknown_items.iter().collect::<String>()
+                )
+            }
+        );
+    }
+    let groups = validate_groups(input);
+    for report in &groups {
+        let problem = if report.size != 3 {
+            format!("incomplete group of {}", report.size)
+        } else {
+            IntersectionError::single(report.items.clone())
+                .expect_err("valid groups are not reported")
+                .to_string()
+        };
+        println!("group starting at line {}: {}", report.first_line, problem);
+    }
+    println!(
+        "Found {} invalid rucksacks and {} invalid groups",
+        rucksacks.len(),
+        groups.len()
+    );
+}
+
+/// Maps every known item to its priority, so that unknown items are not silently ignored.
+struct PriorityTable(HashMap<char, u32>);
+
+impl PriorityTable {
+    /// The priorities are assigned in the order of the alphabet, starting with 1.
+    fn from_alphabet(alphabet: &str) -> Self {
+        let mut table = HashMap::new();
+        for (index, item) in alphabet.chars().enumerate() {
+            let previous = table.insert(item, index as u32 + 1);
+            assert!(
+                previous.is_none(),
+                "item {} appears twice in the alphabet",
+                item
+            );
+        }
+        Self(table)
+    }
+
+    fn priority(&self, item: char) -> Result<u32, UnknownItem> {
+        self.0.get(&item).copied().ok_or(UnknownItem(item))
+    }
+
+    fn unknown_items(&self, rucksack: &Rucksack) -> Vec<char> {
+        rucksack
+            .0
+            .iter()
+            .copied()
+            .filter(|item| !self.0.contains_key(item))
+            .unique()
+            .collect()
+    }
+}
+
+impl Default for PriorityTable {
+    fn default() -> Self {
+        Self::from_alphabet("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ")
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct UnknownItem(char);
+
+impl std::fmt::Display for UnknownItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "item {:?} has no priority", self.0)
+    }
+}
+
+/// Splits the rucksacks into groups of three which share exactly one item, regardless of their order.
+/// `visit` is called with the index triples of every partition as soon as it is found, as there may
+/// be too many to collect, and can stop the search by returning `ControlFlow::Break`. Returns the
+/// number of partitions visited, zero means that none exists.
+fn visit_badge_partitions(
+    rucksacks: &[Rucksack],
+    mut visit: impl FnMut(&[[usize; 3]]) -> ControlFlow<()>,
+) -> usize {
+    if !rucksacks.len().is_multiple_of(3) {
+        return 0;
+    }
+    // the triples are checked on bit sets, with one bit per distinct item in the input
+    let items = rucksacks
+        .iter()
+        .flat_map(|rucksack| rucksack.0.iter().copied())
+        .collect::<HashSet<char>>()
+        .into_iter()
+        .enumerate()
+        .map(|(bit, item)| (item, bit))
+        .collect::<HashMap<char, usize>>();
+    let words = items.len().div_ceil(128);
+    let sets = rucksacks
+        .iter()
+        .map(|rucksack| {
+            let mut set = vec![0u128; words];
+            for item in &rucksack.0 {
+                set[items[item] / 128] |= 1 << (items[item] % 128);
+            }
+            set
+        })
+        .collect::<Vec<_>>();
+    let mut groups = vec![];
+    let mut pair = vec![0u128; words];
+    for first in 0..sets.len() {
+        for second in first + 1..sets.len() {
+            for (word, (a, b)) in pair.iter_mut().zip(sets[first].iter().zip(&sets[second])) {
+                *word = a & b;
+            }
+            if pair.iter().all(|&word| word == 0) {
+                continue;
+            }
+            for (third, set) in sets.iter().enumerate().skip(second + 1) {
+                let common: u32 = pair
+                    .iter()
+                    .zip(set)
+                    .map(|(a, b)| (a & b).count_ones())
+                    .sum();
+                if common == 1 {
+                    groups.push([first, second, third]);
+                }
+            }
+        }
+    }
+    let mut groups_of = vec![vec![]; sets.len()];
+    for group in &groups {
+        for &rucksack in group {
+            groups_of[rucksack].push(*group);
+        }
+    }
+    let mut search = PartitionSearch {
+        groups_of,
+        used: vec![false; sets.len()],
+        current: vec![],
+        found: 0,
+    };
+    let _ = search.run(&mut visit);
+    search.found
+}
+
+struct PartitionSearch {
+    /// The valid groups each rucksack is part of.
+    groups_of: Vec<Vec<[usize; 3]>>,
+    used: Vec<bool>,
+    current: Vec<[usize; 3]>,
+    found: usize,
+}
 
-        let [same_one] = intersection[..] else { panic!("there should only be one intersecion") };
-        **same_one
+impl PartitionSearch {
+    fn is_free(&self, group: &[usize; 3]) -> bool {
+        group.iter().all(|&rucksack| !self.used[rucksack])
+    }
+
+    fn run(&mut self, visit: &mut impl FnMut(&[[usize; 3]]) -> ControlFlow<()>) -> ControlFlow<()> {
+        // every free rucksack has to end up in some group, so branching on the one with the fewest
+        // free groups is enough, and a rucksack without any proves that this branch is a dead end
+        let mut fewest: Option<(usize, usize)> = None;
+        for rucksack in (0..self.used.len()).filter(|&rucksack| !self.used[rucksack]) {
+            let free = self.groups_of[rucksack]
+                .iter()
+                .filter(|group| self.is_free(group))
+                .count();
+            if free == 0 {
+                return ControlFlow::Continue(());
+            }
+            if fewest.is_none_or(|(_, count)| free < count) {
+                fewest = Some((rucksack, free));
+            }
+        }
+        let Some((rucksack, _)) = fewest else {
+            self.found += 1;
+            return visit(&self.current);
+        };
+        for index in 0..self.groups_of[rucksack].len() {
+            let group = self.groups_of[rucksack][index];
+            if !self.is_free(&group) {
+                continue;
+            }
+            group.iter().for_each(|&member| self.used[member] = true);
+            self.current.push(group);
+            let flow = self.run(visit);
+            self.current.pop();
+            group.iter().for_each(|&member| self.used[member] = false);
+            flow?;
+        }
+        ControlFlow::Continue(())
     }
 }
 
-fn char_to_number(input: char) -> u32 {
-    match input {
-        i @ 'a'..='z' => i as u32 - 96,
-        i @ 'A'..='Z' => i as u32 - 38,
-        _ => 0,
+fn print_partitions(input: &str, table: &PriorityTable, limit: usize) {
+    let rucksacks = input.lines().map(Rucksack::from_str).collect::<Vec<_>>();
+    let mut printed = 0;
+    let found = visit_badge_partitions(&rucksacks, |partition| {
+        let groups = partition
+            .iter()
+            .map(|[a, b, c]| format!("{}-{}-{}", a + 1, b + 1, c + 1))
+            .join(", ");
+        let sum: u32 = partition
+            .iter()
+            .map(|[a, b, c]| {
+                let badge = Rucksack::common_one(&rucksacks[*a], &rucksacks[*b], &rucksacks[*c])
+                    .expect("partitions only contain valid groups");
+                table
+                    .priority(badge)
+                    .expect("badges should have a priority")
+            })
+            .sum();
+        println!("{} (sum of badges {})", groups, sum);
+        if printed + 1 == limit {
+            return ControlFlow::Break(());
+        }
+        printed += 1;
+        ControlFlow::Continue(())
+    });
+    if found == 0 {
+        println!("The rucksacks cannot be split into badge groups");
+    } else if found == limit {
+        println!(
+            "Stopped after the first {} partitions into badge groups, use --limit for more",
+            found
+        );
+    } else {
+        println!("Found {} partitions into badge groups", found);
     }
 }
 
+fn print_rearrangements(input: &str) {
+    let mut total = 0;
+    for (index, line) in input.lines().enumerate() {
+        let rucksack = Rucksack::from_str(line);
+        match rucksack.rearrangement() {
+            None => println!("line {}: cannot be rearranged", index + 1),
+            Some(swaps) if swaps.is_empty() => println!("line {}: nothing to swap", index + 1),
+            Some(swaps) => {
+                let plan = swaps
+                    .iter()
+                    .map(|swap| {
+                        format!(
+                            "{}@{} <-> {}@{}",
+                            rucksack.0[swap.left],
+                            swap.left + 1,
+                            rucksack.0[swap.right],
+                            swap.right + 1
+                        )
+                    })
+                    .join(", ");
+                println!("line {}: {}", index + 1, plan);
+                total += swaps.len();
+            }
+        }
+    }
+    println!("The rearrangement needs {} swaps in total", total);
+}
+
 fn main() {
-    use itertools::Itertools;
     let input = include_str!("input.txt");
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let table = match args.iter().position(|arg| arg == "--alphabet") {
+        Some(index) => {
+            let alphabet = args
+                .get(index + 1)
+                .expect("--alphabet needs the items in order");
+            let table = PriorityTable::from_alphabet(alphabet);
+            args.drain(index..=index + 1);
+            table
+        }
+        None => PriorityTable::default(),
+    };
+    match args.first().map(String::as_str) {
+        Some("validate") => return print_validation(input, &table),
+        Some("partition") => {
+            let limit = match args.get(1).map(String::as_str) {
+                Some("--limit") => args
+                    .get(2)
+                    .and_then(|limit| limit.parse().ok())
+                    .filter(|&limit| limit > 0)
+                    .expect("--limit needs a positive number of partitions"),
+                _ => 10,
+            };
+            return print_partitions(input, &table, limit);
+        }
+        Some("rearrange") => return print_rearrangements(input),
+        _ => {}
+    }
     let sum: u32 = input
         .lines()
-        .map(|line| char_to_number(Rucksack::from_str(line).odd_one()))
+        .map(|line| {
+            let odd_one = Rucksack::from_str(line)
+                .odd_one()
+                .expect("should have one odd item, run with `validate` for details");
+            table.priority(odd_one).unwrap_or_else(|e| panic!("{}", e))
+        })
         .sum();
 
     println!("The sum of wrong entries is {}", sum);
@@ -57,7 +529,9 @@ fn main() {
             let a = Rucksack::from_str(chunk.next().unwrap());
             let b = Rucksack::from_str(chunk.next().unwrap());
             let c = Rucksack::from_str(chunk.next().unwrap());
-            char_to_number(Rucksack::common_one(&a, &b, &c))
+            let badge = Rucksack::common_one(&a, &b, &c)
+                .expect("should have one badge, run with `validate` for details");
+            table.priority(badge).unwrap_or_else(|e| panic!("{}", e))
         })
         .sum();
 
@@ -68,23 +542,176 @@ fn main() {
 mod tests {
     use super::*;
 
+    fn badge_partitions(rucksacks: &[Rucksack]) -> Vec<Vec<[usize; 3]>> {
+        let mut partitions = vec![];
+        visit_badge_partitions(rucksacks, |partition| {
+            partitions.push(partition.to_vec());
+            ControlFlow::Continue(())
+        });
+        partitions
+    }
+
     #[test]
     fn should_find_odd_ones() {
         assert_eq!(
             Rucksack::from_str("vJrwpWtwJgWrhcsFMMfFFhFp").odd_one(),
-            'p'
+            Ok('p')
         );
         assert_eq!(
             Rucksack::from_str("jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL").odd_one(),
-            'L'
+            Ok('L')
         );
-        assert_eq!(Rucksack::from_str("PmmdzqPrVvPwwTWBwg").odd_one(), 'P');
+        assert_eq!(Rucksack::from_str("PmmdzqPrVvPwwTWBwg").odd_one(), Ok('P'));
     }
 
     #[test]
     fn should_convert_to_number() {
-        assert_eq!(char_to_number('p'), 16);
-        assert_eq!(char_to_number('L'), 38);
-        assert_eq!(char_to_number('P'), 42);
+        let table = PriorityTable::default();
+
+        assert_eq!(table.priority('p'), Ok(16));
+        assert_eq!(table.priority('L'), Ok(38));
+        assert_eq!(table.priority('P'), Ok(42));
+        assert_eq!(table.priority('1'), Err(UnknownItem('1')));
+    }
+
+    #[test]
+    fn should_use_custom_alphabet() {
+        let table = PriorityTable::from_alphabet("0123€");
+        let rucksack = Rucksack::from_str("01€3€2");
+
+        assert_eq!(table.priority(rucksack.odd_one().unwrap()), Ok(5));
+        assert_eq!(table.priority('a'), Err(UnknownItem('a')));
+        assert_eq!(
+            table.unknown_items(&Rucksack::from_str("0a1ba")),
+            vec!['a', 'b']
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_rucksacks() {
+        let input = "vJrwpWtwJgWrhcsFMMfFFhFp\nabcdef\nabcabc\nabcXa\na1ba";
+
+        assert_eq!(
+            validate_rucksacks(input, &PriorityTable::default()),
+            vec![
+                RucksackReport {
+                    line: 2,
+                    items: vec![],
+                    odd_length: false,
+                    unknown_items: vec![]
+                },
+                RucksackReport {
+                    line: 3,
+                    items: vec!['a', 'b', 'c'],
+                    odd_length: false,
+                    unknown_items: vec![]
+                },
+                RucksackReport {
+                    line: 4,
+                    items: vec!['a'],
+                    odd_length: true,
+                    unknown_items: vec![]
+                },
+                RucksackReport {
+                    line: 5,
+                    items: vec!['a'],
+                    odd_length: false,
+                    unknown_items: vec!['1']
+                },
+            ]
+        );
+        assert_eq!(
+            Rucksack::from_str("abcdef").odd_one(),
+            Err(IntersectionError::Missing)
+        );
+    }
+
+    #[test]
+    fn should_report_invalid_groups() {
+        let input = "abc\nabd\nabe\naxy\nazw\navu\nabc";
+
+        assert_eq!(
+            validate_groups(input),
+            vec![
+                GroupReport {
+                    first_line: 1,
+                    size: 3,
+                    items: vec!['a', 'b']
+                },
+                GroupReport {
+                    first_line: 7,
+                    size: 1,
+                    items: vec![]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn should_find_badge_partitions() {
+        let rucksacks = [
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "ttgJtRGJQctTZtZT",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "PmmdzqPrVvPwwTWBwg",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+        ]
+        .map(Rucksack::from_str);
+
+        let partitions = badge_partitions(&rucksacks);
+
+        assert!(partitions.contains(&vec![[0, 2, 4], [1, 3, 5]]));
+        assert!(partitions
+            .iter()
+            .flatten()
+            .all(
+                |[a, b, c]| Rucksack::common_one(&rucksacks[*a], &rucksacks[*b], &rucksacks[*c])
+                    .is_ok()
+            ));
+    }
+
+    #[test]
+    fn should_prove_missing_badge_partitions() {
+        let disjoint = ["ab", "cd", "ef"].map(Rucksack::from_str);
+        let incomplete = ["ab", "ab"].map(Rucksack::from_str);
+
+        assert!(badge_partitions(&disjoint).is_empty());
+        assert!(badge_partitions(&incomplete).is_empty());
+    }
+
+    #[test]
+    fn should_stop_badge_partitions_early() {
+        // 24 rucksacks sharing only `a` split in millions of ways, the lone `z` makes every one fail
+        let mut rucksacks = ["a"; 24]
+            .map(Rucksack::from_str)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let visited = visit_badge_partitions(&rucksacks, |_| ControlFlow::Break(()));
+        assert_eq!(visited, 1);
+
+        rucksacks.extend(["z", "a", "a"].map(Rucksack::from_str));
+        assert!(badge_partitions(&rucksacks).is_empty());
+    }
+
+    #[test]
+    fn should_plan_minimal_rearrangement() {
+        let mut rucksack = Rucksack::from_str("vJrwpWtwJgWrhcsFMMfFFhFp");
+
+        let swaps = rucksack.rearrangement().expect("can be rearranged");
+        for swap in &swaps {
+            rucksack.0.swap(swap.left, swap.right);
+        }
+
+        assert_eq!(swaps.len(), 1);
+        assert!(rucksack.compartment_intersection().is_empty());
+        assert_eq!(Rucksack::from_str("abcd").rearrangement(), Some(vec![]));
+        assert_eq!(
+            Rucksack::from_str("abab")
+                .rearrangement()
+                .map(|swaps| swaps.len()),
+            Some(1)
+        );
+        assert_eq!(Rucksack::from_str("aaab").rearrangement(), None);
     }
 }
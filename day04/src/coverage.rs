@@ -0,0 +1,136 @@
+use crate::interval_set::IntervalSet;
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+const SECTIONS_PER_ROW: usize = 50;
+
+/// How many elves cover each section between the first and the last assigned one.
+pub struct Coverage {
+    /// Consecutive sections which are covered by the same number of elves, in order.
+    segments: Vec<(RangeInclusive<usize>, usize)>,
+}
+
+impl Coverage {
+    pub fn new<'a>(ranges: impl IntoIterator<Item = &'a RangeInclusive<usize>>) -> Self {
+        let mut changes: BTreeMap<usize, isize> = BTreeMap::new();
+        for range in ranges.into_iter().filter(|range| !range.is_empty()) {
+            *changes.entry(*range.start()).or_default() += 1;
+            *changes.entry(*range.end() + 1).or_default() -= 1;
+        }
+
+        let mut segments = vec![];
+        let mut elves = 0;
+        for ((&start, &change), (&next, _)) in changes.iter().zip(changes.iter().skip(1)) {
+            elves += change;
+            segments.push((start..=next - 1, elves as usize));
+        }
+        Self { segments }
+    }
+
+    fn sections_where(&self, predicate: impl Fn(usize) -> bool) -> IntervalSet<usize> {
+        self.segments
+            .iter()
+            .filter(|(_, elves)| predicate(*elves))
+            .map(|(range, _)| range.clone())
+            .collect()
+    }
+
+    /// Sections between the first and the last assigned section which no elf covers.
+    pub fn uncovered(&self) -> IntervalSet<usize> {
+        self.sections_where(|elves| elves == 0)
+    }
+
+    pub fn covered_by_more_than(&self, limit: usize) -> IntervalSet<usize> {
+        self.sections_where(|elves| elves > limit)
+    }
+
+    /// Whether every section of the range would still be covered without the elf assigned to it.
+    pub fn is_redundant(&self, range: &RangeInclusive<usize>) -> bool {
+        self.covered_by_more_than(1).contains_range(range)
+    }
+
+    fn most_elves(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|(_, elves)| *elves)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// How many elves a step of the histogram or a digit of the drawing stands for, so that large
+    /// teams still fit into ten steps.
+    pub fn scale(&self) -> usize {
+        self.most_elves().div_ceil(9).max(1)
+    }
+
+    /// The step of the histogram and digit of the drawing for a number of elves. Step 0 is only
+    /// used for uncovered sections, step `d` stands for `(d - 1) * scale + 1..=d * scale` elves.
+    pub fn step(&self, elves: usize) -> usize {
+        elves.div_ceil(self.scale())
+    }
+
+    /// Maps each step to the number of sections covered by that many elves.
+    pub fn histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for (range, elves) in &self.segments {
+            *histogram.entry(self.step(*elves)).or_default() += range.end() - range.start() + 1;
+        }
+        histogram
+    }
+
+    /// Draws the section line, each section shows the step of the number of elves covering it.
+    /// Sections without elves are drawn as `.`.
+    pub fn render(&self) -> String {
+        let sections = self
+            .segments
+            .iter()
+            .flat_map(|(range, elves)| range.clone().map(|section| (section, *elves)))
+            .collect::<Vec<_>>();
+        sections
+            .chunks(SECTIONS_PER_ROW)
+            .map(|row| {
+                let line = row
+                    .iter()
+                    .map(|(_, elves)| match elves {
+                        0 => '.',
+                        _ => char::from_digit(self.step(*elves) as u32, 10).unwrap(),
+                    })
+                    .collect::<String>();
+                format!("{:>6} {}\n", row[0].0, line)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_count_coverage() {
+        let coverage = Coverage::new(&[2..=4, 6..=8, 3..=4, 8..=8]);
+
+        assert_eq!(coverage.uncovered().ranges(), &[5..=5]);
+        assert_eq!(coverage.covered_by_more_than(1).ranges(), &[3..=4, 8..=8]);
+        assert_eq!(
+            coverage.histogram(),
+            BTreeMap::from([(0, 1), (1, 3), (2, 3)])
+        );
+        assert!(coverage.is_redundant(&(3..=4)));
+        assert!(!coverage.is_redundant(&(2..=4)));
+        assert_eq!(coverage.render(), "     2 122.112\n");
+    }
+
+    #[test]
+    fn should_scale_large_teams() {
+        let ranges = vec![1..=2; 20];
+        let coverage = Coverage::new(ranges.iter().chain([&(2..=2), &(4..=4)]));
+
+        assert_eq!(coverage.scale(), 3);
+        assert_eq!(
+            coverage.histogram(),
+            BTreeMap::from([(0, 1), (1, 1), (7, 2)])
+        );
+        assert_eq!(coverage.render(), "     1 77.1\n");
+    }
+}
@@ -0,0 +1,205 @@
+use crate::InclusiveRangeExt;
+use std::num::ParseIntError;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// An integer type which can be used as section ID.
+pub trait Section: Ord + Copy + FromStr<Err = ParseIntError> {
+    fn checked_next(self) -> Option<Self>;
+    fn checked_prev(self) -> Option<Self>;
+    /// The number of sections in `start..=end`, saturating at `usize::MAX`.
+    fn count(start: Self, end: Self) -> usize;
+}
+
+macro_rules! impl_section {
+    ($($t:ty),*) => {
+        $(
+            impl Section for $t {
+                fn checked_next(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn checked_prev(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn count(start: Self, end: Self) -> usize {
+                    usize::try_from(end.abs_diff(start))
+                        .ok()
+                        .and_then(|distance| distance.checked_add(1))
+                        .unwrap_or(usize::MAX)
+                }
+            }
+        )*
+    };
+}
+
+impl_section!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A set of sections, stored as sorted ranges which neither overlap nor touch each other.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IntervalSet<T>(Vec<RangeInclusive<T>>);
+
+impl<T: Section> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self(vec![])
+    }
+
+    pub fn from_ranges(ranges: impl IntoIterator<Item = RangeInclusive<T>>) -> Self {
+        let mut ranges = ranges
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|range| *range.start());
+
+        let mut merged: Vec<RangeInclusive<T>> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if Self::touches(last, &range) => {
+                    *last = *last.start()..=*last.end().max(range.end());
+                }
+                _ => merged.push(range),
+            }
+        }
+        Self(merged)
+    }
+
+    fn touches(first: &RangeInclusive<T>, second: &RangeInclusive<T>) -> bool {
+        first.overlaps_range(second)
+            || second.overlaps_range(first)
+            || first.end().checked_next() == Some(*second.start())
+    }
+
+    pub fn ranges(&self) -> &[RangeInclusive<T>] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The number of sections covered by the set.
+    pub fn count(&self) -> usize {
+        self.0
+            .iter()
+            .map(|range| T::count(*range.start(), *range.end()))
+            .fold(0, usize::saturating_add)
+    }
+
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        range.is_empty() || self.0.iter().any(|own| own.contains_range(range))
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::from_ranges(self.0.iter().chain(other.0.iter()).cloned())
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = vec![];
+        let (mut i, mut j) = (0, 0);
+        while let (Some(a), Some(b)) = (self.0.get(i), other.0.get(j)) {
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                result.push(start..=end);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self(result)
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn difference(&self, other: &Self) -> Self {
+        let (Some(first), Some(last)) = (self.0.first(), self.0.last()) else {
+            return Self::new();
+        };
+        self.intersection(&other.complement(*first.start()..=*last.end()))
+    }
+
+    /// All sections within `bounds` which are not in the set.
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> Self {
+        let mut result = vec![];
+        let mut next = Some(*bounds.start());
+        for range in &self.0 {
+            let Some(start) = next else { break };
+            if let Some(end) = range.start().checked_prev() {
+                let end = end.min(*bounds.end());
+                if start <= end {
+                    result.push(start..=end);
+                }
+            }
+            next = next.max(range.end().checked_next());
+            if range.end() >= bounds.end() {
+                next = None;
+            }
+        }
+        if let Some(start) = next {
+            if start <= *bounds.end() {
+                result.push(start..=*bounds.end());
+            }
+        }
+        Self(result)
+    }
+}
+
+impl<T: Section> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Section> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        Self::from_ranges(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_merge_ranges() {
+        let set =
+            IntervalSet::from_ranges([5..=7, 1..=2, 3..=3, 6..=9, RangeInclusive::new(12, 11)]);
+
+        assert_eq!(set.ranges(), &[1..=3, 5..=9]);
+        assert_eq!(set.count(), 8);
+        assert!(set.contains_range(&(6..=9)));
+        assert!(!set.contains_range(&(3..=5)));
+    }
+
+    #[test]
+    fn should_combine_sets() {
+        let a = IntervalSet::from_ranges([2..=4, 6..=8]);
+        let b = IntervalSet::from_ranges([4..=6, 10..=10]);
+
+        assert_eq!(a.union(&b).ranges(), &[2..=8, 10..=10]);
+        assert_eq!(a.intersection(&b).ranges(), &[4..=4, 6..=6]);
+        assert_eq!(a.difference(&b).ranges(), &[2..=3, 7..=8]);
+
+        assert_eq!(a.complement(1..=9).ranges(), &[1..=1, 5..=5, 9..=9]);
+        assert_eq!(a.complement(3..=7).ranges(), &[5..=5]);
+    }
+
+    #[test]
+    fn should_handle_type_bounds() {
+        let set = IntervalSet::from_ranges([i8::MIN..=-1, 0..=i8::MAX]);
+
+        assert_eq!(set.ranges(), &[i8::MIN..=i8::MAX]);
+        assert_eq!(set.count(), 256);
+        assert!(set.complement(i8::MIN..=i8::MAX).is_empty());
+        assert_eq!(
+            IntervalSet::from_ranges([0..=9u8])
+                .complement(0..=u8::MAX)
+                .ranges(),
+            &[10..=u8::MAX]
+        );
+    }
+}
@@ -1,4 +1,12 @@
+mod coverage;
+mod interval_set;
+mod reassignment;
+
+use coverage::Coverage;
+use interval_set::{IntervalSet, Section};
 use itertools::Itertools;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
 use std::ops::RangeInclusive;
 
 trait InclusiveRangeExt {
@@ -7,7 +15,7 @@ trait InclusiveRangeExt {
     fn from_str(input: &str) -> Self;
 }
 
-impl InclusiveRangeExt for RangeInclusive<usize> {
+impl<T: Section> InclusiveRangeExt for RangeInclusive<T> {
     fn contains_range(&self, other: &Self) -> bool {
         self.contains(other.start()) && self.contains(other.end())
     }
@@ -20,32 +28,260 @@ impl InclusiveRangeExt for RangeInclusive<usize> {
         let (start, end) = input
             .split('-')
             .map(|a| a.parse().expect("should be able to parse"))
-            .collect_tuple::<(usize, usize)>()
+            .collect_tuple::<(T, T)>()
             .unwrap();
 
         start..=end
     }
 }
 
-fn main() {
-    let input = include_str!("input.txt");
-    let x = input
-        .lines()
-        .map(|line| {
-            let (range1, range2) = line
-                .split(',')
-                .collect_tuple::<(&str, &str)>()
-                .expect("exactly two");
-            (
-                RangeInclusive::from_str(range1),
-                RangeInclusive::from_str(range2),
-            )
+/// The assignments of all elves on one line.
+type Group = Vec<RangeInclusive<usize>>;
+
+fn parse_group(line: &str) -> Group {
+    line.split(',').map(RangeInclusive::from_str).collect()
+}
+
+/// Pairs of members `(a, b)` of the group where the range of `a` contains the range of `b`.
+fn containing_members(group: &Group) -> Vec<(usize, usize)> {
+    (0..group.len())
+        .cartesian_product(0..group.len())
+        .filter(|(a, b)| a != b && group[*a].contains_range(&group[*b]))
+        .collect()
+}
+
+/// Pairs of members `(a, b)` of the group with `a < b` whose ranges overlap.
+fn overlapping_members(group: &Group) -> Vec<(usize, usize)> {
+    (0..group.len())
+        .tuple_combinations()
+        .filter(|(a, b)| {
+            group[*a].overlaps_range(&group[*b]) || group[*b].overlaps_range(&group[*a])
+        })
+        .collect()
+}
+
+/// Identifies an elf by the line of its assignment and its member index within that line, both
+/// 0-based.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Elf {
+    line: usize,
+    member: usize,
+}
+
+impl Display for Elf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.line + 1, self.member + 1)
+    }
+}
+
+fn elves(groups: &[Group]) -> Vec<(Elf, &RangeInclusive<usize>)> {
+    groups
+        .iter()
+        .enumerate()
+        .flat_map(|(line, group)| {
+            group
+                .iter()
+                .enumerate()
+                .map(move |(member, range)| (Elf { line, member }, range))
         })
+        .collect()
+}
+
+/// Finds every pair of elves in the whole file whose assignments overlap, with a sweep over the
+/// range starts. Only elves whose range has not ended yet are kept, so this needs O(n log n + pairs).
+fn overlapping_pairs(groups: &[Group]) -> Vec<(Elf, Elf)> {
+    let elves = elves(groups)
+        .into_iter()
+        .sorted_by_key(|(_, range)| *range.start())
+        .collect::<Vec<_>>();
+
+    let mut active: BTreeSet<(usize, Elf)> = BTreeSet::new();
+    let mut result = vec![];
+    for (elf, range) in elves {
+        while active.first().is_some_and(|(end, _)| end < range.start()) {
+            active.pop_first();
+        }
+        result.extend(
+            active
+                .iter()
+                .map(|(_, other)| (elf.min(*other), elf.max(*other))),
+        );
+        active.insert((*range.end(), elf));
+    }
+    result.sort();
+    result
+}
+
+fn print_overlapping_pairs(groups: &[Group]) {
+    let range_of = |elf: Elf| &groups[elf.line][elf.member];
+    let overlapping = overlapping_pairs(groups);
+    for (a, b) in &overlapping {
+        let relation = if range_of(*a).contains_range(range_of(*b)) {
+            "contains"
+        } else if range_of(*b).contains_range(range_of(*a)) {
+            "is contained in"
+        } else {
+            "overlaps"
+        };
+        println!("{} {} {}", a, relation, b);
+    }
+    println!(
+        "Number of overlapping pairs of elves in the whole file is {}",
+        overlapping.len()
+    );
+}
+
+fn print_ranges(ranges: &IntervalSet<usize>) -> String {
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+    ranges
+        .ranges()
+        .iter()
+        .map(|range| format!("{}-{}", range.start(), range.end()))
+        .join(", ")
+}
+
+fn print_coverage(groups: &[Group], limit: usize) {
+    let elves = elves(groups);
+    let coverage = Coverage::new(elves.iter().map(|(_, range)| *range));
+
+    let scale = coverage.scale();
+    let histogram = coverage.histogram();
+    let most_sections = histogram.values().copied().max().unwrap_or(0).max(1);
+    println!("Sections by number of elves covering them:");
+    for (step, sections) in &histogram {
+        let elves = match step {
+            0 => "0".to_string(),
+            _ => format!("{}-{}", (step - 1) * scale + 1, step * scale),
+        };
+        println!(
+            "{:>13} elves {:>6} {}",
+            elves,
+            sections,
+            "*".repeat((sections * 50).div_ceil(most_sections))
+        );
+    }
+    println!(
+        "Section line, each digit stands for up to {} elves:\n{}",
+        scale,
+        coverage.render()
+    );
+    println!(
+        "Sections covered by no elf: {}",
+        print_ranges(&coverage.uncovered())
+    );
+    println!(
+        "Sections covered by more than {} elves: {}",
+        limit,
+        print_ranges(&coverage.covered_by_more_than(limit))
+    );
+    let redundant = elves
+        .iter()
+        .filter(|(_, range)| coverage.is_redundant(range))
+        .map(|(elf, _)| elf)
         .collect::<Vec<_>>();
+    println!(
+        "{} elves are redundant on their own given the rest of the team: {}",
+        redundant.len(),
+        redundant.iter().join(", ")
+    );
+}
+
+fn print_group(group: &Group) -> String {
+    group
+        .iter()
+        .map(|range| format!("{}-{}", range.start(), range.end()))
+        .join(",")
+}
+
+/// Prints the assignments in the input format with the elves of every line moved apart, the
+/// summary goes to stderr so that the output can be used as input again.
+fn print_reassignment(groups: &[Group]) {
+    let ranges = || groups.iter().flatten();
+    let (Some(start), Some(end)) = (
+        ranges().map(|range| *range.start()).min(),
+        ranges().map(|range| *range.end()).max(),
+    ) else {
+        return;
+    };
+    let bounds = start..=end;
+    let (mut shifted, mut moved_lines, mut stuck_lines) = (0, 0, 0);
+    for group in groups {
+        match reassignment::separate(group, &bounds) {
+            Some(reassignment) => {
+                shifted += reassignment.shifted;
+                moved_lines += usize::from(reassignment.shifted > 0);
+                println!("{}", print_group(&reassignment.ranges));
+            }
+            None => {
+                stuck_lines += 1;
+                println!("{}", print_group(group));
+            }
+        }
+    }
+    eprintln!(
+        "Shifted {} sections on {} lines, {} lines do not fit into sections {}-{} without overlap",
+        shifted,
+        moved_lines,
+        stuck_lines,
+        bounds.start(),
+        bounds.end()
+    );
+}
+
+fn print_groups(groups: &[Group]) {
+    for (line, group) in groups.iter().enumerate() {
+        let containing = containing_members(group)
+            .into_iter()
+            .map(|(a, b)| format!("{} contains {}", a + 1, b + 1));
+        let overlapping = overlapping_members(group)
+            .into_iter()
+            .map(|(a, b)| format!("{} overlaps {}", a + 1, b + 1));
+        let relations = containing.chain(overlapping).join(", ");
+        if !relations.is_empty() {
+            println!("line {}: {}", line + 1, relations);
+        }
+    }
+}
+
+fn print_sections(groups: &[Group]) {
+    let assigned = groups.iter().flatten().cloned().collect::<IntervalSet<_>>();
+    let (Some(first), Some(last)) = (assigned.ranges().first(), assigned.ranges().last()) else {
+        return println!("No sections are assigned");
+    };
+    let unassigned = assigned.complement(*first.start()..=*last.end());
+
+    println!(
+        "The elves cover {} sections and leave {} sections in between unassigned",
+        assigned.count(),
+        unassigned.count()
+    );
+}
+
+fn main() {
+    let input = include_str!("input.txt");
+    let x = input.lines().map(parse_group).collect::<Vec<_>>();
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.first().map(String::as_str) {
+        Some("groups") => return print_groups(&x),
+        Some("pairs") => return print_overlapping_pairs(&x),
+        Some("reassign") => return print_reassignment(&x),
+        Some("sections") => return print_sections(&x),
+        Some("coverage") => {
+            let limit = args.get(1).map_or(2, |limit| {
+                limit
+                    .parse()
+                    .expect("the limit should be a number of elves")
+            });
+            return print_coverage(&x, limit);
+        }
+        _ => {}
+    }
     let number_of_inclusive_ranges = x
         .clone()
         .iter()
-        .filter(|(range1, range2)| range1.contains_range(range2) || range2.contains_range(range1))
+        .filter(|group| !containing_members(group).is_empty())
         .count();
 
     println!(
@@ -55,7 +291,7 @@ fn main() {
     let number_of_overlapping_ranges = x
         .clone()
         .iter()
-        .filter(|(range1, range2)| range1.overlaps_range(range2) || range2.overlaps_range(range1))
+        .filter(|group| !overlapping_members(group).is_empty())
         .count();
 
     println!(
@@ -63,3 +299,43 @@ fn main() {
         number_of_overlapping_ranges
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_all_overlapping_pairs() {
+        let groups = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8,1-1"
+            .lines()
+            .map(parse_group)
+            .collect::<Vec<_>>();
+        let elves = elves(&groups);
+        let expected = elves
+            .iter()
+            .tuple_combinations()
+            .filter(|((_, a), (_, b))| a.overlaps_range(b) || b.overlaps_range(a))
+            .map(|((a, _), (b, _))| (*a.min(b), *a.max(b)))
+            .sorted()
+            .collect::<Vec<_>>();
+
+        assert_eq!(overlapping_pairs(&groups), expected);
+        assert!(overlapping_pairs(&groups)
+            .contains(&(Elf { line: 2, member: 0 }, Elf { line: 2, member: 1 })));
+    }
+
+    #[test]
+    fn should_find_involved_members() {
+        let group = parse_group("2-8,3-7,9-9,6-10,3-7");
+
+        assert_eq!(
+            containing_members(&group),
+            vec![(0, 1), (0, 4), (1, 4), (3, 2), (4, 1)]
+        );
+        assert_eq!(
+            overlapping_members(&group),
+            vec![(0, 1), (0, 3), (0, 4), (1, 3), (1, 4), (2, 3), (3, 4)]
+        );
+        assert!(containing_members(&parse_group("1-2,3-4,5-6")).is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+use std::ops::RangeInclusive;
+
+/// The assignments of a group after they were moved apart, in their original order, with the
+/// number of sections they were shifted by in total.
+#[derive(Debug, PartialEq)]
+pub struct Reassignment {
+    pub ranges: Vec<RangeInclusive<usize>>,
+    pub shifted: usize,
+}
+
+/// Shifts the ranges of a group apart until no two share a section, keeping their lengths and
+/// staying within `bounds`, with the fewest sections shifted in total. Returns `None` if the ranges
+/// do not fit next to each other.
+///
+/// The best order of the ranges depends on their lengths, so the sections are swept from left to
+/// right keeping the cheapest layout for every subset of ranges placed so far. This is exponential
+/// in the size of the group, which stays small, and linear in the sections swept. Among equally
+/// cheap layouts the one with the smallest single shift is taken, then the one ending furthest left.
+pub fn separate(
+    ranges: &[RangeInclusive<usize>],
+    bounds: &RangeInclusive<usize>,
+) -> Option<Reassignment> {
+    let lengths = ranges
+        .iter()
+        .map(|range| range.end() - range.start() + 1)
+        .collect::<Vec<_>>();
+    let total = lengths.iter().sum::<usize>();
+    // in a cheapest layout no range is further than the whole group away from the original ones,
+    // otherwise all ranges beyond the first free section could move one closer
+    let (Some(first), Some(last)) = (
+        ranges.iter().map(|range| *range.start()).min(),
+        ranges.iter().map(|range| *range.end()).max(),
+    ) else {
+        return Some(Reassignment {
+            ranges: vec![],
+            shifted: 0,
+        });
+    };
+    let low = first.saturating_sub(total).max(*bounds.start());
+    let high = last.saturating_add(total).min(*bounds.end());
+    if low > high {
+        return None;
+    }
+    let width = high - low + 1;
+
+    // cost[placed][free] is the total and largest shift of the cheapest layout of the ranges in the
+    // bit set `placed` which all end before section `low + free`, and placed_last[placed][free] the
+    // range ending right there
+    let subsets = 1 << ranges.len();
+    let mut cost = vec![vec![None::<(usize, usize)>; width + 1]; subsets];
+    let mut placed_last = vec![vec![None::<usize>; width + 1]; subsets];
+    cost[0].fill(Some((0, 0)));
+    for free in 1..=width {
+        for placed in 1..subsets {
+            let mut best = cost[placed][free - 1];
+            for (index, length) in lengths.iter().enumerate() {
+                if placed & 1 << index == 0 || *length > free {
+                    continue;
+                }
+                let start = free - length;
+                let Some((total, largest)) = cost[placed ^ 1 << index][start] else {
+                    continue;
+                };
+                let shift = (low + start).abs_diff(*ranges[index].start());
+                let candidate = (total + shift, largest.max(shift));
+                if best.is_none_or(|best| candidate < best) {
+                    best = Some(candidate);
+                    placed_last[placed][free] = Some(index);
+                }
+            }
+            cost[placed][free] = best;
+        }
+    }
+
+    let (shifted, _) = cost[subsets - 1][width]?;
+    let mut starts = vec![0; ranges.len()];
+    let (mut placed, mut free) = (subsets - 1, width);
+    while placed != 0 {
+        match placed_last[placed][free] {
+            Some(index) => {
+                free -= lengths[index];
+                starts[index] = low + free;
+                placed ^= 1 << index;
+            }
+            None => free -= 1,
+        }
+    }
+    Some(Reassignment {
+        shifted,
+        ranges: starts
+            .iter()
+            .zip(&lengths)
+            .map(|(start, length)| *start..=start + length - 1)
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn should_separate_pairs() {
+        let bounds = 1..=9;
+
+        assert_eq!(
+            separate(&[2..=4, 6..=8], &bounds),
+            Some(Reassignment {
+                ranges: vec![2..=4, 6..=8],
+                shifted: 0
+            })
+        );
+        assert_eq!(
+            separate(&[5..=7, 7..=9], &bounds),
+            Some(Reassignment {
+                ranges: vec![4..=6, 7..=9],
+                shifted: 1
+            })
+        );
+        assert_eq!(
+            separate(&[6..=6, 4..=6], &bounds),
+            Some(Reassignment {
+                ranges: vec![6..=6, 3..=5],
+                shifted: 1
+            })
+        );
+        assert_eq!(
+            separate(&[3..=9, 4..=4], &bounds),
+            Some(Reassignment {
+                ranges: vec![3..=9, 2..=2],
+                shifted: 2
+            })
+        );
+        assert_eq!(separate(&[2..=8, 3..=7], &bounds), None);
+    }
+
+    #[test]
+    fn should_separate_groups() {
+        let bounds = 1..=9;
+
+        assert_eq!(
+            separate(&[2..=4, 4..=6, 1..=1], &bounds),
+            Some(Reassignment {
+                ranges: vec![2..=4, 5..=7, 1..=1],
+                shifted: 1
+            })
+        );
+        assert_eq!(
+            separate(&[1..=3, 2..=4, 3..=5], &bounds),
+            Some(Reassignment {
+                ranges: vec![1..=3, 4..=6, 7..=9],
+                shifted: 6
+            })
+        );
+        assert_eq!(separate(&[1..=3, 2..=4, 3..=5, 9..=9], &bounds), None);
+        assert_eq!(
+            separate(&[0..=1, 1..=1], &(0..=9)),
+            Some(Reassignment {
+                ranges: vec![0..=1, 2..=2],
+                shifted: 1
+            })
+        );
+        assert_eq!(
+            separate(&[5..=5], &bounds),
+            Some(Reassignment {
+                ranges: vec![5..=5],
+                shifted: 0
+            })
+        );
+    }
+    #[test]
+    fn should_shift_the_fewest_sections() {
+        // pushing the ranges apart in the order of their midpoints shifts 3 and 6 sections
+        assert_eq!(
+            separate(&[4..=4, 4..=4, 4..=4], &(1..=9)),
+            Some(Reassignment {
+                ranges: vec![5..=5, 4..=4, 3..=3],
+                shifted: 2
+            })
+        );
+        assert_eq!(
+            separate(&[2..=3, 3..=5, 4..=4, 4..=4], &(1..=9)).map(|result| result.shifted),
+            Some(4)
+        );
+    }
+
+    /// The fewest sections shifted by trying every start for every range.
+    fn cheapest(
+        ranges: &[RangeInclusive<usize>],
+        bounds: &RangeInclusive<usize>,
+        placed: &mut Vec<RangeInclusive<usize>>,
+    ) -> Option<usize> {
+        let Some((range, rest)) = ranges.split_first() else {
+            return Some(0);
+        };
+        let length = range.end() - range.start();
+        let mut best = None;
+        for start in *bounds.start()..=bounds.end().saturating_sub(length) {
+            let moved = start..=start + length;
+            if placed
+                .iter()
+                .any(|other| other.start() <= moved.end() && moved.start() <= other.end())
+            {
+                continue;
+            }
+            placed.push(moved);
+            if let Some(rest) = cheapest(rest, bounds, placed) {
+                let total = start.abs_diff(*range.start()) + rest;
+                best = Some(best.map_or(total, |best: usize| best.min(total)));
+            }
+            placed.pop();
+        }
+        best
+    }
+
+    #[test]
+    fn should_shift_as_little_as_trying_every_layout() {
+        let bounds = 1..=6;
+        let all_ranges = (0..=7)
+            .flat_map(|start| (start..=7).map(move |end| start..=end))
+            .collect::<Vec<_>>();
+
+        for size in 1..=3 {
+            for group in
+                std::iter::repeat_n(all_ranges.iter().cloned(), size).multi_cartesian_product()
+            {
+                let expected = cheapest(&group, &bounds, &mut vec![]);
+                let reassignment = separate(&group, &bounds);
+                assert_eq!(
+                    reassignment
+                        .as_ref()
+                        .map(|reassignment| reassignment.shifted),
+                    expected,
+                    "{:?}",
+                    group
+                );
+                let Some(Reassignment { ranges, shifted }) = reassignment else {
+                    continue;
+                };
+                assert_eq!(
+                    ranges
+                        .iter()
+                        .zip(&group)
+                        .map(|(moved, range)| moved.start().abs_diff(*range.start()))
+                        .sum::<usize>(),
+                    shifted
+                );
+                for (index, moved) in ranges.iter().enumerate() {
+                    assert!(bounds.contains(moved.start()) && bounds.contains(moved.end()));
+                    assert_eq!(
+                        moved.end() - moved.start(),
+                        group[index].end() - group[index].start()
+                    );
+                    assert!(ranges[index + 1..]
+                        .iter()
+                        .all(|other| moved.end() < other.start() || other.end() < moved.start()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::{ExecutionError, Instruction, Stack, StackError};
+
+/// A crane model which decides how the crates of an instruction end up on the target stack.
+pub trait Crane {
+    fn name(&self) -> String;
+    /// Leaves the stack unchanged if the instruction cannot be executed.
+    fn execute(&mut self, stack: &mut Stack, instruction: Instruction) -> Result<(), StackError>;
+    /// Whether an instruction can behave differently depending on the instructions before it.
+    fn depends_on_history(&self) -> bool {
+        false
+    }
+}
+
+/// Moves one crate after the other, so the moved crates end up in reverse order.
+pub struct CrateMover9000;
+
+impl Crane for CrateMover9000 {
+    fn name(&self) -> String {
+        "mover 9000".to_string()
+    }
+
+    fn execute(&mut self, stack: &mut Stack, instruction: Instruction) -> Result<(), StackError> {
+        stack.calclulate_instruction_for_mover_9000(instruction)
+    }
+}
+
+/// Moves all crates at once, so they keep their order.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9001 {
+    fn name(&self) -> String {
+        "mover 9001".to_string()
+    }
+
+    fn execute(&mut self, stack: &mut Stack, instruction: Instruction) -> Result<(), StackError> {
+        stack.calclulate_instruction_for_mover_9001(instruction)
+    }
+}
+
+/// Grabs at most `max_lift` crates at once, every grab keeps the order of its crates.
+pub struct LimitedLift {
+    max_lift: u32,
+}
+
+impl Crane for LimitedLift {
+    fn name(&self) -> String {
+        format!("crane lifting {} crates", self.max_lift)
+    }
+
+    fn execute(&mut self, stack: &mut Stack, instruction: Instruction) -> Result<(), StackError> {
+        // checked up front, so that a failing instruction does not leave some grabs done
+        stack.validate(instruction)?;
+        let mut remaining = instruction.times;
+        while remaining > 0 {
+            let times = remaining.min(self.max_lift);
+            stack.calclulate_instruction_for_mover_9001(Instruction {
+                times,
+                ..instruction
+            })?;
+            remaining -= times;
+        }
+        Ok(())
+    }
+}
+
+/// Moves all crates at once like the mover 9001, but flips every second batch upside down.
+#[derive(Default)]
+pub struct FlippingCrane {
+    flip_next: bool,
+}
+
+impl Crane for FlippingCrane {
+    fn name(&self) -> String {
+        "flipping crane".to_string()
+    }
+
+    fn execute(&mut self, stack: &mut Stack, instruction: Instruction) -> Result<(), StackError> {
+        if self.flip_next {
+            stack.calclulate_instruction_for_mover_9000(instruction)?;
+        } else {
+            stack.calclulate_instruction_for_mover_9001(instruction)?;
+        }
+        self.flip_next = !self.flip_next;
+        Ok(())
+    }
+
+    fn depends_on_history(&self) -> bool {
+        true
+    }
+}
+
+/// Selects a crane by the name used on the command line: `9000`, `9001`, `limited:<crates>` or
+/// `flipping`.
+pub fn crane_by_name(name: &str) -> Option<Box<dyn Crane>> {
+    match name {
+        "9000" => Some(Box::new(CrateMover9000)),
+        "9001" => Some(Box::new(CrateMover9001)),
+        "flipping" => Some(Box::new(FlippingCrane::default())),
+        _ => {
+            let max_lift = name.strip_prefix("limited:")?.parse().ok()?;
+            (max_lift > 0).then(|| Box::new(LimitedLift { max_lift }) as Box<dyn Crane>)
+        }
+    }
+}
+
+/// Stops at the first instruction which fails, the stack keeps the state before that instruction.
+pub fn run(
+    stack: &mut Stack,
+    instructions: &[Instruction],
+    crane: &mut dyn Crane,
+) -> Result<(), ExecutionError> {
+    for (index, instruction) in instructions.iter().enumerate() {
+        crane
+            .execute(stack, *instruction)
+            .map_err(|error| ExecutionError { index, error })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{example_instructions, example_stack};
+    use std::collections::BTreeMap;
+
+    fn run_example(crane: &str) -> String {
+        let mut stack = example_stack();
+        let mut crane = crane_by_name(crane).expect("known crane");
+
+        run(&mut stack, &example_instructions(), crane.as_mut()).expect("valid instructions");
+        stack.print()
+    }
+
+    #[test]
+    fn should_run_cranes_by_name() {
+        assert_eq!(run_example("9000"), "CMZ");
+        assert_eq!(run_example("9001"), "MCD");
+        assert_eq!(run_example("limited:1"), "CMZ");
+        assert_eq!(run_example("limited:3"), "MCD");
+        assert_eq!(run_example("limited:2"), "MCZ");
+        assert_eq!(run_example("flipping"), "MCZ");
+        assert!(crane_by_name("limited:0").is_none());
+        assert!(crane_by_name("9002").is_none());
+    }
+
+    #[test]
+    fn should_stop_at_failing_instruction() {
+        let mut stack = Stack(BTreeMap::from([(1, vec!['Z', 'N']), (2, vec!['M'])]));
+        let instructions = [
+            Instruction::new(1, 1, 2),
+            Instruction::new(3, 2, 1),
+            Instruction::new(1, 2, 1),
+        ];
+
+        assert_eq!(
+            run(&mut stack, &instructions, &mut LimitedLift { max_lift: 1 }),
+            Err(ExecutionError {
+                index: 1,
+                error: StackError::NotEnoughCrates {
+                    stack: 2,
+                    requested: 3,
+                    available: 2
+                }
+            })
+        );
+        assert_eq!(stack.print(), "ZN");
+    }
+}
@@ -0,0 +1,122 @@
+use crate::Stack;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+impl Stack {
+    /// Builds the stacks from the crate drawing of the puzzle, where every row lists the crates of
+    /// one level and the last line numbers the stacks. The crate of a stack is found in the same
+    /// column as its number, so stacks of any width and height can be read.
+    pub fn from_drawing(drawing: &str) -> Self {
+        let mut lines = drawing.lines().rev();
+        let footer = lines.next().expect("drawing should have a footer");
+        let mut labels = vec![];
+        let mut stacks: BTreeMap<u32, Vec<char>> = BTreeMap::new();
+        let footer = footer.chars().collect::<Vec<_>>();
+        for (position, c) in footer.iter().enumerate() {
+            if c.is_whitespace() || position > 0 && !footer[position - 1].is_whitespace() {
+                continue;
+            }
+            let number = footer[position..]
+                .iter()
+                .take_while(|c| !c.is_whitespace())
+                .collect::<String>()
+                .parse()
+                .expect("stack numbers should be parseable");
+            stacks.insert(number, vec![]);
+            labels.push((number, position));
+        }
+        for row in lines {
+            let row = row.chars().collect::<Vec<_>>();
+            for (number, position) in &labels {
+                match row.get(*position) {
+                    Some(c) if c.is_alphanumeric() => stacks.get_mut(number).unwrap().push(*c),
+                    _ => {}
+                }
+            }
+        }
+        Stack(stacks)
+    }
+}
+
+/// Draws the stacks the same way as the puzzle input, so that the result can be read by
+/// [`Stack::from_drawing`] again. Every column is wide enough for its crates and its number.
+impl Display for Stack {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let labels = self
+            .0
+            .keys()
+            .map(|number| number.to_string())
+            .collect::<Vec<_>>();
+        let widths = labels
+            .iter()
+            .map(|label| (label.len() + 1).max(3))
+            .collect::<Vec<_>>();
+        let height = self.0.values().map(Vec::len).max().unwrap_or(0);
+        for level in (0..height).rev() {
+            let row = self
+                .0
+                .values()
+                .zip(&widths)
+                .map(|(crates, width)| match crates.get(level) {
+                    Some(c) => format!("{:<width$}", format!("[{}]", c), width = width),
+                    None => " ".repeat(*width),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            writeln!(f, "{}", row)?;
+        }
+        let footer = labels
+            .iter()
+            .zip(&widths)
+            .map(|(label, width)| format!(" {:<width$}", label, width = width - 1))
+            .collect::<Vec<_>>()
+            .join(" ");
+        write!(f, "{}", footer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_drawing() {
+        let drawing = "    [D]
+[N] [C]
+[Z] [M] [P]
+ 1   2   3 ";
+
+        assert_eq!(
+            Stack::from_drawing(drawing).0,
+            BTreeMap::from([
+                (1, vec!['Z', 'N']),
+                (2, vec!['M', 'C', 'D']),
+                (3, vec!['P']),
+            ])
+        );
+    }
+
+    #[test]
+    fn should_draw_stack() {
+        let drawing = "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ";
+        let stack = Stack::from_drawing(drawing);
+
+        assert_eq!(stack.to_string(), drawing);
+        assert_eq!(Stack::from_drawing(&stack.to_string()).0, stack.0);
+    }
+
+    #[test]
+    fn should_draw_wide_stack() {
+        let stack = Stack(BTreeMap::from([
+            (9, vec!['A']),
+            (10, vec![]),
+            (100, vec!['B', 'C']),
+        ]));
+
+        assert_eq!(
+            stack.to_string(),
+            "        [C] \n[A]     [B] \n 9   10  100"
+        );
+        assert_eq!(Stack::from_drawing(&stack.to_string()).0, stack.0);
+    }
+}
@@ -1,10 +1,20 @@
+mod crane;
+mod drawing;
+mod optimiser;
+mod planner;
+mod reverse;
+mod simulator;
+
 use regex::Regex;
+use simulator::Simulator;
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::time::Instant;
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Stack(BTreeMap<u32, Vec<char>>);
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct Instruction {
     times: u32,
     from: u32,
@@ -17,8 +27,86 @@ impl Instruction {
     }
 }
 
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "move {} from {} to {}", self.times, self.from, self.to)
+    }
+}
+
+/// Why an instruction cannot be executed on a stack.
+#[derive(Debug, PartialEq)]
+enum StackError {
+    UnknownStack(u32),
+    NotEnoughCrates {
+        stack: u32,
+        requested: u32,
+        available: usize,
+    },
+    SameStack(u32),
+}
+
+impl Display for StackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StackError::UnknownStack(stack) => write!(f, "there is no stack {}", stack),
+            StackError::NotEnoughCrates {
+                stack,
+                requested,
+                available,
+            } => write!(
+                f,
+                "stack {} has only {} crates but {} are requested",
+                stack, available, requested
+            ),
+            StackError::SameStack(stack) => {
+                write!(f, "crates are moved from stack {} onto itself", stack)
+            }
+        }
+    }
+}
+
+/// A failed instruction with its 0-based index in the instruction list.
+#[derive(Debug, PartialEq)]
+struct ExecutionError {
+    index: usize,
+    error: StackError,
+}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "instruction {} failed: {}", self.index + 1, self.error)
+    }
+}
+
 impl Stack {
-    fn calclulate_instruction_for_mover_9000(&mut self, instruction: Instruction) {
+    /// Checks everything which could make the instruction fail, so that it can be executed
+    /// afterwards without leaving the stack half changed.
+    fn validate(&self, instruction: Instruction) -> Result<(), StackError> {
+        let from = self
+            .0
+            .get(&instruction.from)
+            .ok_or(StackError::UnknownStack(instruction.from))?;
+        if !self.0.contains_key(&instruction.to) {
+            return Err(StackError::UnknownStack(instruction.to));
+        }
+        if instruction.from == instruction.to {
+            return Err(StackError::SameStack(instruction.from));
+        }
+        if from.len() < instruction.times as usize {
+            return Err(StackError::NotEnoughCrates {
+                stack: instruction.from,
+                requested: instruction.times,
+                available: from.len(),
+            });
+        }
+        Ok(())
+    }
+
+    fn calclulate_instruction_for_mover_9000(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<(), StackError> {
+        self.validate(instruction)?;
         for _ in 0..instruction.times {
             let x = self
                 .0
@@ -32,9 +120,14 @@ impl Stack {
                 .expect("we know the stacks")
                 .push(x);
         }
+        Ok(())
     }
 
-    fn calclulate_instruction_for_mover_9001(&mut self, instruction: Instruction) {
+    fn calclulate_instruction_for_mover_9001(
+        &mut self,
+        instruction: Instruction,
+    ) -> Result<(), StackError> {
+        self.validate(instruction)?;
         let old_stack = self
             .0
             .get_mut(&instruction.from)
@@ -45,28 +138,21 @@ impl Stack {
             .get_mut(&instruction.to)
             .expect("we know the stacks")
             .append(&mut splitted);
+        Ok(())
     }
 
-    fn print(self) -> String {
-        self.0.iter().map(|(_, v)| v.last().unwrap()).collect()
+    fn print(&self) -> String {
+        self.0.values().filter_map(|v| v.last()).collect()
     }
 }
 fn main() {
-    let mut stack = Stack(BTreeMap::from([
-        (1, vec!['H', 'T', 'Z', 'D']),
-        (2, vec!['Q', 'R', 'W', 'T', 'G', 'C', 'S']),
-        (3, vec!['P', 'B', 'F', 'Q', 'N', 'R', 'C', 'H']),
-        (4, vec!['L', 'C', 'N', 'F', 'H', 'Z']),
-        (5, vec!['G', 'L', 'F', 'Q', 'S']),
-        (6, vec!['V', 'P', 'W', 'Z', 'B', 'R', 'C', 'S']),
-        (7, vec!['Z', 'F', 'J']),
-        (8, vec!['D', 'L', 'V', 'Z', 'R', 'H', 'Q']),
-        (9, vec!['B', 'H', 'G', 'N', 'F', 'Z', 'L', 'D']),
-    ]));
-    let mut stack2 = stack.clone();
     let input = include_str!("input.txt");
+    let (drawing, moves) = input
+        .split_once("\n\n")
+        .expect("the drawing should be separated from the moves by a blank line");
+    let stack = Stack::from_drawing(drawing);
     let re = Regex::new(r"move (\d+) from (\d+) to (\d+)").unwrap();
-    let instructions = input
+    let instructions = moves
         .lines()
         .map(|line| {
             let captures = re.captures(line).expect("wrong line format");
@@ -77,60 +163,240 @@ fn main() {
             )
         })
         .collect::<Vec<_>>();
-    instructions
-        .clone()
-        .into_iter()
-        .for_each(|instruction| stack.calclulate_instruction_for_mover_9000(instruction));
-
-    println!(
-        "The stack has the code '{}' when calculated for the mover 9000",
-        stack.print()
-    );
-
-    instructions
-        .clone()
-        .into_iter()
-        .for_each(|instruction| stack2.calclulate_instruction_for_mover_9001(instruction));
-
-    println!(
-        "The stack has the code '{}' when calculated for the mover 9001",
-        stack2.print()
-    );
+
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    // every mode builds its cranes from these names, modes with a single crane take the first
+    let crane_names = match args.iter().position(|arg| arg == "--crane") {
+        Some(index) => {
+            let name = args
+                .get(index + 1)
+                .expect("--crane needs the name of a crane");
+            if crane::crane_by_name(name).is_none() {
+                panic!("unknown crane {}", name);
+            }
+            vec![name.as_str()]
+        }
+        None => vec!["9000", "9001"],
+    };
+    let new_crane = |name: &str| crane::crane_by_name(name).expect("crane names are checked");
+    let mut cranes = crane_names
+        .iter()
+        .map(|name| new_crane(name))
+        .collect::<Vec<_>>();
+
+    match args.first().map(String::as_str) {
+        Some("draw") => {
+            let crane = cranes.remove(0);
+            let mut simulator = Simulator::new(stack, instructions, crane);
+            let (frames, error) = simulator.frames();
+            frames.iter().for_each(|frame| println!("{}\n", frame));
+            if let Some(error) = error {
+                println!("{}", error);
+            }
+            return;
+        }
+        Some("fast") => {
+            let check = args.iter().any(|arg| arg == "--check");
+            for (keeps_order, mut crane) in [
+                (
+                    false,
+                    Box::new(crane::CrateMover9000) as Box<dyn crane::Crane>,
+                ),
+                (true, Box::new(crane::CrateMover9001)),
+            ] {
+                let start = Instant::now();
+                let fast = reverse::top_crates(&stack, &instructions, keeps_order);
+                let fast_time = start.elapsed();
+                match &fast {
+                    Ok(code) => println!(
+                        "The {} ends with '{}', tracked back in {:?}",
+                        crane.name(),
+                        code,
+                        fast_time
+                    ),
+                    Err(error) => println!("The {} stopped, {}", crane.name(), error),
+                }
+                if check {
+                    let mut simulated = stack.clone();
+                    let start = Instant::now();
+                    let simulation = crane::run(&mut simulated, &instructions, crane.as_mut())
+                        .map(|_| simulated.print());
+                    let simulation_time = start.elapsed();
+                    if simulation == fast {
+                        println!("The simulation agrees in {:?}", simulation_time);
+                    } else {
+                        println!("The simulation disagrees: {:?}", simulation);
+                    }
+                }
+            }
+            return;
+        }
+        Some("plan") => {
+            let target = match (args.get(1).map(String::as_str), args.get(2)) {
+                (Some("top"), Some(code)) => planner::Target::TopCrates(code.clone()),
+                (Some("arrangement"), Some(path)) => {
+                    let drawing = std::fs::read_to_string(path).expect("target should be readable");
+                    planner::Target::Arrangement(Stack::from_drawing(
+                        drawing.trim_end_matches('\n'),
+                    ))
+                }
+                _ => panic!("plan needs a target: `top <code>` or `arrangement <drawing file>`"),
+            };
+            let limit = args
+                .iter()
+                .position(|arg| arg == "--limit")
+                .and_then(|index| args.get(index + 1))
+                .map_or(1_000_000, |limit| {
+                    limit.parse().expect("limit should be a number")
+                });
+            match planner::plan(&stack, &target, cranes[0].as_mut(), limit) {
+                Ok(plan) => {
+                    println!("{}\n", stack);
+                    plan.iter()
+                        .for_each(|instruction| println!("{}", instruction));
+                }
+                Err(error) => eprintln!("No plan for the {}: {}", cranes[0].name(), error),
+            }
+            return;
+        }
+        Some("optimise") => {
+            let new_crane = || new_crane(crane_names[0]);
+            match optimiser::optimise(&stack, &instructions, &new_crane) {
+                Ok(optimised) => {
+                    println!("{}\n", stack);
+                    optimised
+                        .iter()
+                        .for_each(|instruction| println!("{}", instruction));
+                    eprintln!(
+                        "Saved {} of {} instructions for the {}",
+                        instructions.len() - optimised.len(),
+                        instructions.len(),
+                        new_crane().name()
+                    );
+                }
+                Err(error) => eprintln!("{}", error),
+            }
+            return;
+        }
+        Some("debug") => {
+            let crane = cranes.remove(0);
+            let mut simulator = Simulator::new(stack, instructions, crane);
+            simulator.interact(std::io::stdin().lock());
+            return;
+        }
+        _ => {}
+    }
+
+    for crane in &mut cranes {
+        let mut stack = stack.clone();
+        if let Err(error) = crane::run(&mut stack, &instructions, crane.as_mut()) {
+            println!("The {} stopped, {}", crane.name(), error);
+            continue;
+        }
+
+        println!(
+            "The stack has the code '{}' when calculated for the {}",
+            stack.print(),
+            crane.name()
+        );
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_stack_9000() {
-        let mut stack = Stack(BTreeMap::from([
+    /// The stacks of the puzzle example.
+    pub(crate) fn example_stack() -> Stack {
+        Stack(BTreeMap::from([
             (1, vec!['Z', 'N']),
             (2, vec!['M', 'C', 'D']),
             (3, vec!['P']),
-        ]));
+        ]))
+    }
 
-        stack.calclulate_instruction_for_mover_9000(Instruction::new(1, 2, 1));
-        stack.calclulate_instruction_for_mover_9000(Instruction::new(3, 1, 3));
-        stack.calclulate_instruction_for_mover_9000(Instruction::new(2, 2, 1));
-        stack.calclulate_instruction_for_mover_9000(Instruction::new(1, 1, 2));
+    /// The instructions of the puzzle example.
+    pub(crate) fn example_instructions() -> Vec<Instruction> {
+        vec![
+            Instruction::new(1, 2, 1),
+            Instruction::new(3, 1, 3),
+            Instruction::new(2, 2, 1),
+            Instruction::new(1, 1, 2),
+        ]
+    }
+
+    #[test]
+    fn test_stack_9000() {
+        let mut stack = example_stack();
+
+        stack
+            .calclulate_instruction_for_mover_9000(Instruction::new(1, 2, 1))
+            .unwrap();
+        stack
+            .calclulate_instruction_for_mover_9000(Instruction::new(3, 1, 3))
+            .unwrap();
+        stack
+            .calclulate_instruction_for_mover_9000(Instruction::new(2, 2, 1))
+            .unwrap();
+        stack
+            .calclulate_instruction_for_mover_9000(Instruction::new(1, 1, 2))
+            .unwrap();
 
         assert_eq!(stack.print(), "CMZ".to_string())
     }
 
     #[test]
     fn test_stack_9001() {
-        let mut stack = Stack(BTreeMap::from([
-            (1, vec!['Z', 'N']),
-            (2, vec!['M', 'C', 'D']),
-            (3, vec!['P']),
-        ]));
+        let mut stack = example_stack();
 
-        stack.calclulate_instruction_for_mover_9001(Instruction::new(1, 2, 1));
-        stack.calclulate_instruction_for_mover_9001(Instruction::new(3, 1, 3));
-        stack.calclulate_instruction_for_mover_9001(Instruction::new(2, 2, 1));
-        stack.calclulate_instruction_for_mover_9001(Instruction::new(1, 1, 2));
+        stack
+            .calclulate_instruction_for_mover_9001(Instruction::new(1, 2, 1))
+            .unwrap();
+        stack
+            .calclulate_instruction_for_mover_9001(Instruction::new(3, 1, 3))
+            .unwrap();
+        stack
+            .calclulate_instruction_for_mover_9001(Instruction::new(2, 2, 1))
+            .unwrap();
+        stack
+            .calclulate_instruction_for_mover_9001(Instruction::new(1, 1, 2))
+            .unwrap();
 
         assert_eq!(stack.print(), "MCD".to_string())
     }
+
+    #[test]
+    fn test_invalid_instructions() {
+        let mut stack = Stack(BTreeMap::from([(1, vec!['Z', 'N']), (2, vec!['M'])]));
+
+        assert_eq!(
+            stack.calclulate_instruction_for_mover_9001(Instruction::new(3, 1, 2)),
+            Err(StackError::NotEnoughCrates {
+                stack: 1,
+                requested: 3,
+                available: 2
+            })
+        );
+        assert_eq!(
+            stack.calclulate_instruction_for_mover_9000(Instruction::new(3, 1, 2)),
+            Err(StackError::NotEnoughCrates {
+                stack: 1,
+                requested: 3,
+                available: 2
+            })
+        );
+        assert_eq!(
+            stack.calclulate_instruction_for_mover_9000(Instruction::new(1, 4, 2)),
+            Err(StackError::UnknownStack(4))
+        );
+        assert_eq!(
+            stack.calclulate_instruction_for_mover_9000(Instruction::new(1, 1, 4)),
+            Err(StackError::UnknownStack(4))
+        );
+        assert_eq!(
+            stack.calclulate_instruction_for_mover_9001(Instruction::new(1, 2, 2)),
+            Err(StackError::SameStack(2))
+        );
+        assert_eq!(stack.print(), "NM".to_string())
+    }
 }
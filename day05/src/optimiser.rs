@@ -0,0 +1,110 @@
+use crate::crane::{self, Crane};
+use crate::{ExecutionError, Instruction, Stack};
+
+/// Shortens an instruction list without changing the final arrangement. Cancelling moves are
+/// dropped, consecutive moves along the same route are merged and every other instruction is
+/// tried without. Each change is only kept if a simulation with a fresh crane from `new_crane`
+/// still ends with the same arrangement, so this works for every crane model.
+pub fn optimise(
+    start: &Stack,
+    instructions: &[Instruction],
+    new_crane: &dyn Fn() -> Box<dyn Crane>,
+) -> Result<Vec<Instruction>, ExecutionError> {
+    let expected = simulate(start, instructions, new_crane)?;
+    let is_equivalent = |candidate: &[Instruction]| {
+        simulate(start, candidate, new_crane).is_ok_and(|stack| stack == expected)
+    };
+
+    let mut optimised = instructions.to_vec();
+    let mut changed = true;
+    while changed {
+        changed = false;
+        let mut index = 0;
+        while index < optimised.len() {
+            let candidates = candidates(&optimised, index);
+            match candidates
+                .into_iter()
+                .find(|candidate| is_equivalent(candidate))
+            {
+                Some(candidate) => {
+                    optimised = candidate;
+                    changed = true;
+                }
+                None => index += 1,
+            }
+        }
+    }
+    Ok(optimised)
+}
+
+/// Shorter versions of the list which only change the instructions at and right after `index`.
+fn candidates(instructions: &[Instruction], index: usize) -> Vec<Vec<Instruction>> {
+    let replace = |length: usize, replacement: &[Instruction]| {
+        let mut candidate = instructions[..index].to_vec();
+        candidate.extend_from_slice(replacement);
+        candidate.extend_from_slice(&instructions[index + length..]);
+        candidate
+    };
+    let mut candidates = vec![];
+    if let Some([first, second]) = instructions.get(index..index + 2) {
+        if first.from == second.to && first.to == second.from {
+            candidates.push(replace(2, &[]));
+        }
+        if first.from == second.from && first.to == second.to {
+            let times = first.times + second.times;
+            candidates.push(replace(2, &[Instruction { times, ..*first }]));
+        }
+    }
+    candidates.push(replace(1, &[]));
+    candidates
+}
+
+fn simulate(
+    start: &Stack,
+    instructions: &[Instruction],
+    new_crane: &dyn Fn() -> Box<dyn Crane>,
+) -> Result<Stack, ExecutionError> {
+    let mut stack = start.clone();
+    crane::run(&mut stack, instructions, new_crane().as_mut())?;
+    Ok(stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{CrateMover9000, CrateMover9001};
+    use crate::tests::example_stack;
+
+    fn redundant_instructions() -> Vec<Instruction> {
+        vec![
+            Instruction::new(1, 2, 1),
+            Instruction::new(1, 1, 2),
+            Instruction::new(1, 2, 3),
+            Instruction::new(1, 2, 3),
+        ]
+    }
+
+    #[test]
+    fn should_merge_and_cancel_moves() {
+        let optimised = optimise(&example_stack(), &redundant_instructions(), &|| {
+            Box::new(CrateMover9000)
+        });
+
+        assert_eq!(optimised, Ok(vec![Instruction::new(2, 2, 3)]));
+    }
+
+    #[test]
+    fn should_keep_order_sensitive_moves() {
+        let new_crane = || Box::new(CrateMover9001) as Box<dyn Crane>;
+        let optimised = optimise(&example_stack(), &redundant_instructions(), &new_crane).unwrap();
+
+        assert_eq!(
+            optimised,
+            vec![Instruction::new(1, 2, 3), Instruction::new(1, 2, 3)]
+        );
+        assert_eq!(
+            simulate(&example_stack(), &optimised, &new_crane),
+            simulate(&example_stack(), &redundant_instructions(), &new_crane)
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use crate::crane::Crane;
+use crate::{Instruction, Stack};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt::{Display, Formatter};
+
+/// What the stacks should look like at the end of the plan.
+pub enum Target {
+    /// The top crate of every stack in order, like the puzzle answer.
+    TopCrates(String),
+    Arrangement(Stack),
+}
+
+impl Target {
+    fn is_reached(&self, stack: &Stack) -> bool {
+        match self {
+            Target::TopCrates(code) => {
+                stack.0.values().all(|crates| !crates.is_empty()) && stack.print() == *code
+            }
+            Target::Arrangement(target) => stack == target,
+        }
+    }
+
+    /// A lower bound for the number of instructions which are still needed. Every instruction
+    /// changes at most two stacks, so half of the wrong stacks can be fixed per instruction.
+    fn estimate(&self, stack: &Stack) -> usize {
+        let wrong = match self {
+            Target::TopCrates(code) => stack
+                .0
+                .values()
+                .zip(code.chars())
+                .filter(|(crates, top)| crates.last() != Some(top))
+                .count(),
+            Target::Arrangement(target) => stack
+                .0
+                .values()
+                .zip(target.0.values())
+                .filter(|(crates, target)| crates != target)
+                .count(),
+        };
+        wrong.div_ceil(2)
+    }
+
+    /// Rejects targets which no instructions could reach, as the search would never end for them.
+    fn is_reachable_from(&self, stack: &Stack) -> bool {
+        let mut crates = stack.0.values().flatten().copied().collect::<Vec<_>>();
+        crates.sort_unstable();
+        match self {
+            Target::TopCrates(code) => {
+                code.chars().count() == stack.0.len()
+                    && code.chars().all(|top| {
+                        let index = crates.binary_search(&top);
+                        index.map(|index| crates.remove(index)).is_ok()
+                    })
+            }
+            Target::Arrangement(target) => {
+                let mut target_crates = target.0.values().flatten().copied().collect::<Vec<_>>();
+                target_crates.sort_unstable();
+                target.0.keys().eq(stack.0.keys()) && target_crates == crates
+            }
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError {
+    /// The crane behaves differently depending on the earlier instructions.
+    UnsupportedCrane,
+    Unreachable,
+    /// The search visited this many arrangements without reaching the target.
+    LimitReached(usize),
+}
+
+impl Display for PlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanError::UnsupportedCrane => write!(f, "the crane depends on its history"),
+            PlanError::Unreachable => write!(f, "the target cannot be reached"),
+            PlanError::LimitReached(limit) => {
+                write!(f, "no plan found within {} arrangements", limit)
+            }
+        }
+    }
+}
+
+/// Finds a shortest list of instructions which turns `start` into the target with A*.
+/// At most `limit` arrangements are visited.
+pub fn plan(
+    start: &Stack,
+    target: &Target,
+    crane: &mut dyn Crane,
+    limit: usize,
+) -> Result<Vec<Instruction>, PlanError> {
+    if crane.depends_on_history() {
+        return Err(PlanError::UnsupportedCrane);
+    }
+    if !target.is_reachable_from(start) {
+        return Err(PlanError::Unreachable);
+    }
+
+    // every visited arrangement with the instruction leading to it from its parent
+    let mut nodes: Vec<(Stack, Option<(usize, Instruction)>)> = vec![(start.clone(), None)];
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = BinaryHeap::from([Reverse((target.estimate(start), 0, 0))]);
+
+    while let Some(Reverse((_, distance, node))) = queue.pop() {
+        let stack = nodes[node].0.clone();
+        if distances[&stack] < distance {
+            continue;
+        }
+        if target.is_reached(&stack) {
+            return Ok(path(&nodes, node));
+        }
+        for instruction in possible_instructions(&stack) {
+            let mut next = stack.clone();
+            if crane.execute(&mut next, instruction).is_err() {
+                continue;
+            }
+            if distances
+                .get(&next)
+                .is_some_and(|known| *known <= distance + 1)
+            {
+                continue;
+            }
+            if nodes.len() >= limit {
+                return Err(PlanError::LimitReached(limit));
+            }
+            distances.insert(next.clone(), distance + 1);
+            let estimate = distance + 1 + target.estimate(&next);
+            queue.push(Reverse((estimate, distance + 1, nodes.len())));
+            nodes.push((next, Some((node, instruction))));
+        }
+    }
+    Err(PlanError::Unreachable)
+}
+
+fn possible_instructions(stack: &Stack) -> Vec<Instruction> {
+    let mut instructions = vec![];
+    for (from, crates) in &stack.0 {
+        for to in stack.0.keys().filter(|to| *to != from) {
+            for times in 1..=crates.len() as u32 {
+                instructions.push(Instruction::new(times, *from, *to));
+            }
+        }
+    }
+    instructions
+}
+
+fn path(nodes: &[(Stack, Option<(usize, Instruction)>)], mut node: usize) -> Vec<Instruction> {
+    let mut instructions = vec![];
+    while let Some((parent, instruction)) = nodes[node].1 {
+        instructions.push(instruction);
+        node = parent;
+    }
+    instructions.reverse();
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{run, CrateMover9000, CrateMover9001, FlippingCrane};
+    use crate::tests::example_stack;
+
+    fn reachable_within(stack: &Stack, target: &Target, moves: usize) -> bool {
+        target.is_reached(stack)
+            || moves > 0
+                && possible_instructions(stack).into_iter().any(|instruction| {
+                    let mut next = stack.clone();
+                    next.calclulate_instruction_for_mover_9000(instruction)
+                        .unwrap();
+                    reachable_within(&next, target, moves - 1)
+                })
+    }
+
+    #[test]
+    fn should_find_shortest_plan_for_top_crates() {
+        let target = Target::TopCrates("CMZ".to_string());
+
+        let instructions = plan(&example_stack(), &target, &mut CrateMover9000, 100_000).unwrap();
+        let mut stack = example_stack();
+        run(&mut stack, &instructions, &mut CrateMover9000).unwrap();
+
+        assert!(target.is_reached(&stack));
+        assert!(!reachable_within(
+            &example_stack(),
+            &target,
+            instructions.len() - 1
+        ));
+    }
+
+    #[test]
+    fn should_find_plan_for_arrangement() {
+        let mut target = example_stack();
+        target
+            .calclulate_instruction_for_mover_9001(Instruction::new(2, 2, 3))
+            .unwrap();
+
+        assert_eq!(
+            plan(
+                &example_stack(),
+                &Target::Arrangement(target),
+                &mut CrateMover9001,
+                1000
+            ),
+            Ok(vec![Instruction::new(2, 2, 3)])
+        );
+        assert_eq!(
+            plan(
+                &example_stack(),
+                &Target::Arrangement(example_stack()),
+                &mut CrateMover9001,
+                1000
+            ),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn should_reject_impossible_plans() {
+        let target = Target::TopCrates("XMZ".to_string());
+
+        assert_eq!(
+            plan(&example_stack(), &target, &mut CrateMover9000, 1000),
+            Err(PlanError::Unreachable)
+        );
+        assert_eq!(
+            plan(
+                &example_stack(),
+                &target,
+                &mut FlippingCrane::default(),
+                1000
+            ),
+            Err(PlanError::UnsupportedCrane)
+        );
+        assert_eq!(
+            plan(
+                &example_stack(),
+                &Target::TopCrates("PDN".to_string()),
+                &mut CrateMover9000,
+                5
+            ),
+            Err(PlanError::LimitReached(5))
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{ExecutionError, Instruction, Stack, StackError};
+use std::collections::BTreeMap;
+
+/// Finds the top crates without moving a single crate. Only the heights of the stacks are tracked
+/// forward, then the position of every final top crate is followed backwards through the
+/// instructions to its place in the starting stack. This needs O(instructions * stacks), no matter
+/// how many crates are moved. `keeps_order` is `false` for the mover 9000 and `true` for the 9001.
+pub fn top_crates(
+    stack: &Stack,
+    instructions: &[Instruction],
+    keeps_order: bool,
+) -> Result<String, ExecutionError> {
+    let mut heights = stack
+        .0
+        .iter()
+        .map(|(number, crates)| (*number, crates.len()))
+        .collect::<BTreeMap<_, _>>();
+    // the heights of the source and the target stack before every instruction
+    let mut before = Vec::with_capacity(instructions.len());
+    for (index, instruction) in instructions.iter().enumerate() {
+        let (from, to) = move_heights(&mut heights, *instruction)
+            .map_err(|error| ExecutionError { index, error })?;
+        before.push((from, to));
+    }
+
+    Ok(heights
+        .iter()
+        .filter(|(_, height)| **height > 0)
+        .map(|(number, height)| {
+            let mut number = *number;
+            let mut position = height - 1;
+            for (instruction, (from, to)) in instructions.iter().zip(&before).rev() {
+                let times = instruction.times as usize;
+                if number == instruction.to && position >= *to {
+                    let offset = position - to;
+                    number = instruction.from;
+                    position = if keeps_order {
+                        from - times + offset
+                    } else {
+                        from - 1 - offset
+                    };
+                }
+            }
+            stack.0[&number][position]
+        })
+        .collect())
+}
+
+fn move_heights(
+    heights: &mut BTreeMap<u32, usize>,
+    instruction: Instruction,
+) -> Result<(usize, usize), StackError> {
+    let from = *heights
+        .get(&instruction.from)
+        .ok_or(StackError::UnknownStack(instruction.from))?;
+    let to = *heights
+        .get(&instruction.to)
+        .ok_or(StackError::UnknownStack(instruction.to))?;
+    if instruction.from == instruction.to {
+        return Err(StackError::SameStack(instruction.from));
+    }
+    let times = instruction.times as usize;
+    if from < times {
+        return Err(StackError::NotEnoughCrates {
+            stack: instruction.from,
+            requested: instruction.times,
+            available: from,
+        });
+    }
+    heights.insert(instruction.from, from - times);
+    heights.insert(instruction.to, to + times);
+    Ok((from, to))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::{run, Crane, CrateMover9000, CrateMover9001};
+    use crate::tests::{example_instructions, example_stack};
+
+    fn simulate(stack: &Stack, instructions: &[Instruction], crane: &mut dyn Crane) -> String {
+        let mut stack = stack.clone();
+        run(&mut stack, instructions, crane).expect("valid instructions");
+        stack.print()
+    }
+
+    #[test]
+    fn should_match_example() {
+        let stack = example_stack();
+        let instructions = example_instructions();
+
+        assert_eq!(
+            top_crates(&stack, &instructions, false),
+            Ok("CMZ".to_string())
+        );
+        assert_eq!(
+            top_crates(&stack, &instructions, true),
+            Ok("MCD".to_string())
+        );
+        assert_eq!(
+            top_crates(&stack, &[Instruction::new(4, 1, 2)], true),
+            Err(ExecutionError {
+                index: 0,
+                error: StackError::NotEnoughCrates {
+                    stack: 1,
+                    requested: 4,
+                    available: 2
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn should_match_simulation_for_generated_moves() {
+        let mut seed = 17u64;
+        let mut random = |bound: usize| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 33) as usize % bound
+        };
+        let start = Stack(
+            (1..=5)
+                .map(|number| {
+                    let crates = (0..random(8)).map(|_| (b'A' + random(26) as u8) as char);
+                    (number, crates.collect())
+                })
+                .collect(),
+        );
+        let mut heights = start.0.values().map(Vec::len).collect::<Vec<_>>();
+        let mut instructions = vec![];
+        while instructions.len() < 300 {
+            let from = random(5);
+            let to = (from + 1 + random(4)) % 5;
+            if heights[from] == 0 {
+                continue;
+            }
+            let times = random(heights[from]) + 1;
+            heights[from] -= times;
+            heights[to] += times;
+            instructions.push(Instruction::new(
+                times as u32,
+                from as u32 + 1,
+                to as u32 + 1,
+            ));
+        }
+
+        assert_eq!(
+            top_crates(&start, &instructions, true),
+            Ok(simulate(&start, &instructions, &mut CrateMover9001))
+        );
+        assert_eq!(
+            top_crates(&start, &instructions, false),
+            Ok(simulate(&start, &instructions, &mut CrateMover9000))
+        );
+    }
+}
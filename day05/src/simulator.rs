@@ -0,0 +1,287 @@
+use crate::crane::Crane;
+use crate::{ExecutionError, Instruction, Stack};
+use std::fmt::{Display, Formatter};
+use std::io::BufRead;
+
+/// Runs the instructions step by step and keeps the stack after every step, so that it is possible
+/// to go back and forth without running the crane again.
+pub struct Simulator {
+    instructions: Vec<Instruction>,
+    /// The stack after as many instructions as the index, only filled up to the furthest step.
+    states: Vec<Stack>,
+    crane: Box<dyn Crane>,
+    position: usize,
+}
+
+/// The state of the stacks after `step` instructions, `instruction` is the last one executed.
+#[derive(Debug, PartialEq)]
+pub struct Frame {
+    pub step: usize,
+    pub instruction: Option<Instruction>,
+    pub stack: Stack,
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.instruction {
+            Some(instruction) => writeln!(f, "step {}: {}", self.step, instruction)?,
+            None => writeln!(f, "step {}: start", self.step)?,
+        }
+        write!(f, "{}", self.stack)
+    }
+}
+
+/// A stack which holds different crates at two steps, the crates are listed bottom to top.
+#[derive(Debug, PartialEq)]
+pub struct StackDiff {
+    pub stack: u32,
+    pub before: Vec<char>,
+    pub after: Vec<char>,
+}
+
+impl Display for StackDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "stack {}: {} -> {}",
+            self.stack,
+            self.before.iter().collect::<String>(),
+            self.after.iter().collect::<String>()
+        )
+    }
+}
+
+impl Simulator {
+    pub fn new(stack: Stack, instructions: Vec<Instruction>, crane: Box<dyn Crane>) -> Self {
+        Self {
+            instructions,
+            states: vec![stack],
+            crane,
+            position: 0,
+        }
+    }
+
+    pub fn steps(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn current(&self) -> &Stack {
+        &self.states[self.position]
+    }
+
+    /// Executes the next instruction, or replays it if it was executed before.
+    /// Returns `false` if there is no instruction left.
+    pub fn step_forward(&mut self) -> Result<bool, ExecutionError> {
+        if self.position == self.steps() {
+            return Ok(false);
+        }
+        if self.position + 1 == self.states.len() {
+            let mut stack = self.current().clone();
+            self.crane
+                .execute(&mut stack, self.instructions[self.position])
+                .map_err(|error| ExecutionError {
+                    index: self.position,
+                    error,
+                })?;
+            self.states.push(stack);
+        }
+        self.position += 1;
+        Ok(true)
+    }
+
+    /// Returns `false` if the simulation is already at the start.
+    pub fn step_back(&mut self) -> bool {
+        let moved = self.position > 0;
+        self.position = self.position.saturating_sub(1);
+        moved
+    }
+
+    /// Jumps to the state after `step` instructions. If an instruction on the way fails, the
+    /// simulator stays at the step before it.
+    pub fn jump_to(&mut self, step: usize) -> Result<(), ExecutionError> {
+        let step = step.min(self.steps());
+        while self.states.len() <= step {
+            self.position = self.states.len() - 1;
+            self.step_forward()?;
+        }
+        self.position = step;
+        Ok(())
+    }
+
+    fn state(&mut self, step: usize) -> Result<&Stack, ExecutionError> {
+        let position = self.position;
+        let jumped = self.jump_to(step);
+        self.position = position;
+        jumped?;
+        Ok(&self.states[step.min(self.steps())])
+    }
+
+    /// Lists every stack which differs between the two steps.
+    pub fn diff(&mut self, from: usize, to: usize) -> Result<Vec<StackDiff>, ExecutionError> {
+        let before = self.state(from)?.clone();
+        let after = self.state(to)?;
+        Ok(before
+            .0
+            .iter()
+            .zip(after.0.values())
+            .filter(|((_, before), after)| before != after)
+            .map(|((stack, before), after)| StackDiff {
+                stack: *stack,
+                before: before.clone(),
+                after: after.clone(),
+            })
+            .collect())
+    }
+
+    /// Exports every step of the run, starting with the initial stack. If an instruction fails,
+    /// the steps before it are exported together with the error.
+    pub fn frames(&mut self) -> (Vec<Frame>, Option<ExecutionError>) {
+        let error = self.state(self.steps()).err();
+        let position = self.position;
+        let frames = (0..self.states.len())
+            .map(|step| {
+                self.position = step;
+                self.frame()
+            })
+            .collect();
+        self.position = position;
+        (frames, error)
+    }
+
+    /// Reads debugger commands line by line: `n` (or nothing) steps forward, `b` steps back,
+    /// `j <step>` jumps, `d <step> <step>` shows the differences, `p` prints and `q` quits.
+    pub fn interact(&mut self, input: impl BufRead) {
+        println!("{}", self.frame());
+        for line in input.lines() {
+            let line = line.expect("should be able to read the command");
+            let mut words = line.split_whitespace();
+            let command = words.next().unwrap_or("n");
+            let numbers = words.map(|word| word.parse::<usize>()).collect::<Vec<_>>();
+            let result = match (command, &numbers[..]) {
+                ("n", []) => self.step_forward().map(|_| ()),
+                ("b", []) => {
+                    self.step_back();
+                    Ok(())
+                }
+                ("j", [Ok(step)]) => self.jump_to(*step),
+                ("d", [Ok(from), Ok(to)]) => {
+                    match self.diff(*from, *to) {
+                        Ok(diffs) if diffs.is_empty() => println!("no differences"),
+                        Ok(diffs) => diffs.iter().for_each(|diff| println!("{}", diff)),
+                        Err(error) => println!("{}", error),
+                    }
+                    continue;
+                }
+                ("p", []) => Ok(()),
+                ("q", []) => return,
+                _ => {
+                    println!("unknown command, use n, b, j <step>, d <step> <step>, p or q");
+                    continue;
+                }
+            };
+            match result {
+                Ok(()) => println!("{}", self.frame()),
+                Err(error) => println!("{}", error),
+            }
+        }
+    }
+
+    fn frame(&self) -> Frame {
+        Frame {
+            step: self.position,
+            instruction: self
+                .position
+                .checked_sub(1)
+                .map(|index| self.instructions[index]),
+            stack: self.current().clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crane::CrateMover9000;
+    use crate::tests::{example_instructions, example_stack};
+    use crate::StackError;
+
+    fn example() -> Simulator {
+        Simulator::new(
+            example_stack(),
+            example_instructions(),
+            Box::new(CrateMover9000),
+        )
+    }
+
+    #[test]
+    fn should_step_back_and_forth() {
+        let mut simulator = example();
+
+        assert_eq!(simulator.step_forward(), Ok(true));
+        assert_eq!(simulator.current().print(), "DCP");
+        assert!(simulator.step_back());
+        assert!(!simulator.step_back());
+        assert_eq!(simulator.current().print(), "NDP");
+        assert_eq!(simulator.jump_to(4), Ok(()));
+        assert_eq!(simulator.step_forward(), Ok(false));
+        assert_eq!(simulator.current().print(), "CMZ");
+        assert_eq!(simulator.jump_to(2), Ok(()));
+        assert_eq!(simulator.current().print(), "CZ");
+    }
+
+    #[test]
+    fn should_keep_position_when_failing() {
+        let mut instructions = example_instructions();
+        instructions[1] = Instruction::new(5, 1, 3);
+        let mut simulator = Simulator::new(example_stack(), instructions, Box::new(CrateMover9000));
+
+        assert_eq!(
+            simulator.diff(0, 3),
+            Err(ExecutionError {
+                index: 1,
+                error: StackError::NotEnoughCrates {
+                    stack: 1,
+                    requested: 5,
+                    available: 3
+                }
+            })
+        );
+        assert_eq!(simulator.current().print(), "NDP");
+
+        let (frames, error) = simulator.frames();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].stack.print(), "DCP");
+        assert_eq!(error.map(|error| error.index), Some(1));
+    }
+
+    #[test]
+    fn should_diff_and_export_frames() {
+        let mut simulator = example();
+
+        assert_eq!(
+            simulator.diff(0, 1),
+            Ok(vec![
+                StackDiff {
+                    stack: 1,
+                    before: vec!['Z', 'N'],
+                    after: vec!['Z', 'N', 'D']
+                },
+                StackDiff {
+                    stack: 2,
+                    before: vec!['M', 'C', 'D'],
+                    after: vec!['M', 'C']
+                },
+            ])
+        );
+        assert_eq!(simulator.current().print(), "NDP");
+
+        let (frames, error) = simulator.frames();
+        assert_eq!(error, None);
+        assert_eq!(frames.len(), 5);
+        assert_eq!(frames[4].instruction, Some(Instruction::new(1, 1, 2)));
+        assert_eq!(
+            frames[1].to_string(),
+            "step 1: move 1 from 2 to 1\n[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 "
+        );
+    }
+}
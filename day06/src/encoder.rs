@@ -0,0 +1,166 @@
+use crate::{MESSAGE_MARKER, PACKET_MARKER};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, PartialEq)]
+pub enum EncodeError {
+    /// The alphabet has fewer different bytes than a message marker needs.
+    AlphabetTooSmall(usize),
+    /// The offsets do not fit the length, or the message marker would contain an earlier packet
+    /// marker. Every message marker ends at least 10 bytes after a packet marker.
+    InvalidOffsets,
+}
+
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::AlphabetTooSmall(size) => write!(
+                f,
+                "the alphabet has {} different bytes but {} are needed",
+                size, MESSAGE_MARKER
+            ),
+            EncodeError::InvalidOffsets => write!(
+                f,
+                "the packet needs to start after at least {} bytes, the message at least {} bytes \
+                 after the packet and both within the stream",
+                PACKET_MARKER,
+                MESSAGE_MARKER - PACKET_MARKER
+            ),
+        }
+    }
+}
+
+/// A small linear congruential generator, so that streams can be reproduced from their seed.
+pub struct Random(pub u64);
+
+impl Random {
+    pub fn below(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) as usize % bound
+    }
+
+    fn shuffled(&mut self, items: &[u8]) -> Vec<u8> {
+        let mut items = items.to_vec();
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index + 1));
+        }
+        items
+    }
+}
+
+/// Generates a random stream of `length` bytes from `alphabet` whose first start-of-packet and
+/// start-of-message markers end exactly after `packet` and `message` bytes. Bytes are picked in
+/// random order and checked against the windows ending at them, backtracking on a dead end.
+pub fn encode(
+    length: usize,
+    alphabet: &[u8],
+    packet: usize,
+    message: usize,
+    seed: u64,
+) -> Result<Vec<u8>, EncodeError> {
+    let mut alphabet = alphabet.to_vec();
+    alphabet.sort_unstable();
+    alphabet.dedup();
+    if alphabet.len() < MESSAGE_MARKER {
+        return Err(EncodeError::AlphabetTooSmall(alphabet.len()));
+    }
+    if packet < PACKET_MARKER
+        || message < packet + MESSAGE_MARKER - PACKET_MARKER
+        || message > length
+    {
+        return Err(EncodeError::InvalidOffsets);
+    }
+
+    let mut random = Random(seed);
+    let mut stream = Vec::with_capacity(length);
+    // the untried bytes for every position up to the next one
+    let mut candidates: Vec<Vec<u8>> = vec![];
+    while stream.len() < length {
+        if candidates.len() == stream.len() {
+            candidates.push(random.shuffled(&alphabet));
+        }
+        match candidates.last_mut().and_then(Vec::pop) {
+            Some(byte) => {
+                stream.push(byte);
+                if !fits(&stream, packet, message) {
+                    stream.pop();
+                }
+            }
+            None => {
+                candidates.pop();
+                stream.pop().expect("valid offsets can always be encoded");
+            }
+        }
+    }
+    Ok(stream)
+}
+
+/// Whether the last byte keeps the markers at their offsets, looking only at windows ending there.
+fn fits(stream: &[u8], packet: usize, message: usize) -> bool {
+    let read = stream.len();
+    let is_distinct = |window: usize| {
+        read >= window && stream[read - window..].iter().collect::<HashSet<_>>().len() == window
+    };
+    let message_start = message - MESSAGE_MARKER;
+    let last = stream[read - 1];
+    if read > message_start && read < message && stream[message_start..read - 1].contains(&last) {
+        return false;
+    }
+    if read < packet {
+        !is_distinct(PACKET_MARKER)
+    } else if read == packet {
+        is_distinct(PACKET_MARKER)
+    } else if read < message {
+        !is_distinct(MESSAGE_MARKER)
+    } else if read == message {
+        is_distinct(MESSAGE_MARKER)
+    } else {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_marker;
+
+    #[test]
+    fn should_place_first_markers_in_generated_streams() {
+        let mut random = Random(6);
+        let alphabet = (b'a'..=b'z').collect::<Vec<_>>();
+        for seed in 0..500 {
+            let alphabet = &alphabet[..MESSAGE_MARKER + random.below(13)];
+            let packet = PACKET_MARKER + random.below(40);
+            let message = packet + MESSAGE_MARKER - PACKET_MARKER + random.below(40);
+            let length = message + random.below(40);
+            let stream = encode(length, alphabet, packet, message, seed).unwrap();
+
+            assert_eq!(stream.len(), length);
+            assert!(stream.iter().all(|byte| alphabet.contains(byte)));
+            assert_eq!(find_marker(&stream, PACKET_MARKER), Some(packet));
+            assert_eq!(find_marker(&stream, MESSAGE_MARKER), Some(message));
+        }
+    }
+
+    #[test]
+    fn should_reject_impossible_streams() {
+        let alphabet = (b'a'..=b'z').collect::<Vec<_>>();
+
+        assert_eq!(
+            encode(30, b"abcabc", 4, 14, 0),
+            Err(EncodeError::AlphabetTooSmall(3))
+        );
+        assert_eq!(
+            encode(30, &alphabet, 5, 14, 0),
+            Err(EncodeError::InvalidOffsets)
+        );
+        assert_eq!(
+            encode(30, &alphabet, 4, 31, 0),
+            Err(EncodeError::InvalidOffsets)
+        );
+        assert_eq!(encode(14, &alphabet, 4, 14, 0).map(|s| s.len()), Ok(14));
+    }
+}
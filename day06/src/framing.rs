@@ -0,0 +1,233 @@
+use crate::{find_marker, MESSAGE_MARKER, PACKET_MARKER};
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::ops::Range;
+
+/// A packet or message with the bytes of its marker and the payload following it. A packet payload
+/// runs up to the next packet marker, a message payload up to the next message marker.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Frame {
+    Packet {
+        marker: Range<usize>,
+        payload: Range<usize>,
+    },
+    /// `packet` is the marker of the last packet starting at or before the message marker.
+    Message {
+        packet: Range<usize>,
+        marker: Range<usize>,
+        payload: Range<usize>,
+    },
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Frame::Packet { marker, payload } => {
+                write!(
+                    f,
+                    "packet with marker {:?} and payload {:?}",
+                    marker, payload
+                )
+            }
+            Frame::Message {
+                packet,
+                marker,
+                payload,
+            } => write!(
+                f,
+                "message of packet {:?} with marker {:?} and payload {:?}",
+                packet, marker, payload
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FramingError {
+    /// The message marker starts before the payload of the first packet.
+    MessageWithoutPacket { marker: Range<usize> },
+}
+
+impl Display for FramingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FramingError::MessageWithoutPacket { marker } => {
+                write!(
+                    f,
+                    "message marker {:?} starts before any packet payload",
+                    marker
+                )
+            }
+        }
+    }
+}
+
+/// Every marker of the given size, each search starts where the last marker ended.
+struct Markers<'a> {
+    input: &'a [u8],
+    window: usize,
+    start: usize,
+}
+
+impl Iterator for Markers<'_> {
+    type Item = Range<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let end = self.start + find_marker(&self.input[self.start..], self.window)?;
+        self.start = end;
+        Some(end - self.window..end)
+    }
+}
+
+/// Walks the packet and message markers of a stream once, yielding the frames in the order of
+/// their marker starts.
+pub struct Frames<'a> {
+    length: usize,
+    packets: Peekable<Markers<'a>>,
+    messages: Peekable<Markers<'a>>,
+    /// The marker of the last packet yielded.
+    packet: Option<Range<usize>>,
+    /// Where the payload of the first packet starts, once that was yielded.
+    first_payload: Option<usize>,
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Result<Frame, FramingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next_packet = match (self.packets.peek(), self.messages.peek()) {
+            (Some(packet), Some(message)) => packet.start <= message.start,
+            (packet, message) => packet.is_some() || message.is_none(),
+        };
+        if next_packet {
+            let marker = self.packets.next()?;
+            let end = self.packets.peek().map_or(self.length, |next| next.start);
+            self.first_payload.get_or_insert(marker.end);
+            self.packet = Some(marker.clone());
+            return Some(Ok(Frame::Packet {
+                payload: marker.end..end,
+                marker,
+            }));
+        }
+
+        let marker = self.messages.next()?;
+        let end = self.messages.peek().map_or(self.length, |next| next.start);
+        // a message marker contains a packet marker, so some packet always starts before it, but
+        // that can be the first one with the message marker starting inside its marker
+        let packet = match (&self.packet, self.first_payload) {
+            (Some(packet), Some(start)) if start <= marker.start => packet.clone(),
+            _ => return Some(Err(FramingError::MessageWithoutPacket { marker })),
+        };
+        Some(Ok(Frame::Message {
+            packet,
+            payload: marker.end..end,
+            marker,
+        }))
+    }
+}
+
+fn markers(input: &[u8], window: usize) -> Markers<'_> {
+    Markers {
+        input,
+        window,
+        start: 0,
+    }
+}
+
+/// Splits the stream into packets and messages. Packet and message markers are both searched for
+/// over the whole stream, every message belongs to the last packet starting at or before it and
+/// may not start before the payload of the first packet.
+pub fn frames(input: &[u8]) -> Frames<'_> {
+    Frames {
+        length: input.len(),
+        packets: markers(input, PACKET_MARKER).peekable(),
+        messages: markers(input, MESSAGE_MARKER).peekable(),
+        packet: None,
+        first_payload: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_find_first_markers_of_examples() {
+        for (input, packet, message) in [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+            ("abcdefghijklmn", 4, 14),
+        ] {
+            let first_packet = markers(input.as_bytes(), PACKET_MARKER).next();
+            let first_message = markers(input.as_bytes(), MESSAGE_MARKER).next();
+
+            assert_eq!(
+                first_packet.map(|marker| marker.end),
+                Some(packet),
+                "{}",
+                input
+            );
+            assert_eq!(
+                first_message.map(|marker| marker.end),
+                Some(message),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn should_attach_messages_to_packets() {
+        let frames = frames(b"abcdaaaaaaaabcdefghijklmnabc").collect::<Vec<_>>();
+
+        assert_eq!(
+            frames[..3],
+            [
+                Ok(Frame::Packet {
+                    marker: 0..4,
+                    payload: 4..11
+                }),
+                Ok(Frame::Packet {
+                    marker: 11..15,
+                    payload: 15..15
+                }),
+                Ok(Frame::Message {
+                    packet: 11..15,
+                    marker: 11..25,
+                    payload: 25..28
+                }),
+            ]
+        );
+        assert_eq!(
+            frames[5],
+            Ok(Frame::Packet {
+                marker: 23..27,
+                payload: 27..28
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_messages_before_packet_payloads() {
+        let frames = frames(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb").collect::<Vec<_>>();
+
+        assert_eq!(frames.len(), 7);
+        assert_eq!(
+            frames[..3],
+            [
+                Ok(Frame::Packet {
+                    marker: 3..7,
+                    payload: 7..7
+                }),
+                Err(FramingError::MessageWithoutPacket { marker: 5..19 }),
+                Ok(Frame::Packet {
+                    marker: 7..11,
+                    payload: 11..11
+                }),
+            ]
+        );
+    }
+}
@@ -1,52 +1,212 @@
+mod encoder;
+mod framing;
+mod scan;
+mod stream;
+
+use std::cell::OnceCell;
 use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::time::Instant;
+
+const PACKET_MARKER: usize = 4;
+const MESSAGE_MARKER: usize = 14;
 
-struct Datastream {
-    raw: &'static str,
-    start_of_packet: usize,
-    start_of_message: usize,
+/// A datastream which is owned (`String`, `Vec<u8>`) or borrowed (`&str`, `&[u8]`). The markers
+/// are searched for on first access and remembered afterwards.
+struct Datastream<T: AsRef<[u8]>> {
+    raw: T,
+    start_of_packet: OnceCell<Result<usize, NoMarker>>,
+    start_of_message: OnceCell<Result<usize, NoMarker>>,
 }
 
-impl Datastream {
-    fn new(input: &'static str) -> Self {
-        use itertools::Itertools;
-        let Some(first_unique_tuple) = input
-            .chars()
-            .into_iter()
-            .enumerate()
-            .tuple_windows()
-            .find(|(a, b, c, d)| HashSet::from([a.1, b.1, c.1, d.1]).len() == 4)
-            else {
-                panic!("No valid start found");
-            };
-        let Some((start_of_message, _)) = input
-            .chars()
-            .collect::<Vec<_>>()
-            .windows(14)
-            .find_position(|w| HashSet::<_>::from_iter(w.iter()).len() == 14)
-            else {
-            panic!("No valid start found");
-        };
+/// No `window` bytes in a row are all different within the `searched` bytes of the stream.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct NoMarker {
+    window: usize,
+    searched: usize,
+}
+
+impl Display for NoMarker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "no marker of {} different bytes within {} bytes",
+            self.window, self.searched
+        )
+    }
+}
 
+impl<T: AsRef<[u8]>> Datastream<T> {
+    fn new(raw: T) -> Self {
         Self {
-            raw: input,
-            start_of_packet: first_unique_tuple.3 .0 + 1,
-            start_of_message: start_of_message + 14,
+            raw,
+            start_of_packet: OnceCell::new(),
+            start_of_message: OnceCell::new(),
         }
     }
+
+    fn start_of_packet(&self) -> Result<usize, NoMarker> {
+        *self
+            .start_of_packet
+            .get_or_init(|| self.find(PACKET_MARKER))
+    }
+
+    fn start_of_message(&self) -> Result<usize, NoMarker> {
+        *self
+            .start_of_message
+            .get_or_init(|| self.find(MESSAGE_MARKER))
+    }
+
+    fn find(&self, window: usize) -> Result<usize, NoMarker> {
+        let raw = self.raw.as_ref();
+        find_marker(raw, window).ok_or(NoMarker {
+            window,
+            searched: raw.len(),
+        })
+    }
 }
 
-fn main() {
-    let input = include_str!("input.txt");
-    let datastream = Datastream::new(input);
-    let start_index = datastream.start_of_packet;
-    let start_of_message = datastream.start_of_message;
+/// Returns the number of bytes read when the last `window` bytes are all different for the first
+/// time. Every byte is looked at once, see [`stream::MarkerDetector`].
+fn find_marker(input: &[u8], window: usize) -> Option<usize> {
+    if window == 0 {
+        return Some(0);
+    }
+    let mut detector = stream::MarkerDetector::new(window);
+    input.iter().find_map(|byte| detector.push(*byte))
+}
+
+/// The former approach which builds a set for every window, kept to compare against.
+fn find_marker_with_sets(input: &str, window: usize) -> Option<usize> {
+    input
+        .chars()
+        .collect::<Vec<_>>()
+        .windows(window)
+        .position(|w| HashSet::<_>::from_iter(w.iter()).len() == window)
+        .map(|position| position + window)
+}
+
+const CAPTURE_SIZE: usize = 64 * 1024 * 1024;
+
+fn benchmark(input: &str) {
+    for window in [PACKET_MARKER, MESSAGE_MARKER] {
+        let start = Instant::now();
+        let with_sets = find_marker_with_sets(input, window);
+        let sets_time = start.elapsed();
+        let start = Instant::now();
+        let linear = find_marker(input.as_bytes(), window);
+        let linear_time = start.elapsed();
+        let start = Instant::now();
+        let skipping = scan::find_marker(input.as_bytes(), window);
+        let skipping_time = start.elapsed();
+        assert_eq!(linear, with_sets, "both scans should find the same marker");
+        assert_eq!(
+            skipping, with_sets,
+            "both scans should find the same marker"
+        );
+        println!(
+            "Marker of {} found at {:?}, with sets in {:?}, in one pass in {:?} and skipping in {:?}",
+            window, linear, sets_time, linear_time, skipping_time
+        );
+    }
 
+    // a large random capture over 13 letters, so that there is no message marker to stop early
+    let mut random = encoder::Random(1);
+    let capture = (0..CAPTURE_SIZE)
+        .map(|_| b'a' + random.below(13) as u8)
+        .collect::<Vec<_>>();
+    let megabytes = CAPTURE_SIZE as f64 / 1024.0 / 1024.0;
+    let start = Instant::now();
+    assert_eq!(find_marker(&capture, MESSAGE_MARKER), None);
+    let linear_time = start.elapsed();
+    let start = Instant::now();
+    assert_eq!(scan::find_marker(&capture, MESSAGE_MARKER), None);
+    let skipping_time = start.elapsed();
     println!(
-        "The datastream starts at index {} and the message at {}!",
-        start_index, start_of_message
+        "Scanned {} MiB in one pass at {:.0} MiB/s and skipping at {:.0} MiB/s",
+        megabytes,
+        megabytes / linear_time.as_secs_f64(),
+        megabytes / skipping_time.as_secs_f64()
     );
 }
 
+fn main() {
+    let input = include_str!("input.txt");
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let alphabet = match args.iter().position(|arg| arg == "--alphabet") {
+        Some(index) => {
+            let alphabet = args
+                .get(index + 1)
+                .expect("--alphabet needs the bytes to encode with")
+                .as_bytes()
+                .to_vec();
+            args.drain(index..=index + 1);
+            alphabet
+        }
+        None => (b'a'..=b'z').collect(),
+    };
+    match args.first().map(String::as_str) {
+        Some("bench") => return benchmark(input),
+        Some("encode") => {
+            let number = |index: usize, default: Option<u64>| {
+                args.get(index)
+                    .map(|arg| arg.parse().expect("arguments should be numbers"))
+                    .or(default)
+                    .expect("encode needs a length, a packet offset and a message offset")
+            };
+            match encoder::encode(
+                number(1, None) as usize,
+                &alphabet,
+                number(2, None) as usize,
+                number(3, None) as usize,
+                number(4, Some(0)),
+            ) {
+                Ok(stream) => println!("{}", String::from_utf8_lossy(&stream)),
+                Err(error) => eprintln!("{}", error),
+            }
+            return;
+        }
+        Some("frames") => {
+            let (mut packets, mut messages, mut errors) = (0, 0, 0);
+            for frame in framing::frames(input.as_bytes()) {
+                match frame {
+                    Ok(frame) => {
+                        match frame {
+                            framing::Frame::Packet { .. } => packets += 1,
+                            framing::Frame::Message { .. } => messages += 1,
+                        }
+                        println!("{}", frame);
+                    }
+                    Err(error) => {
+                        errors += 1;
+                        println!("{}", error);
+                    }
+                }
+            }
+            println!(
+                "Found {} packets and {} messages with {} framing errors",
+                packets, messages, errors
+            );
+            return;
+        }
+        Some("stream") => {
+            stream::StreamDetector::new()
+                .read_from(std::io::stdin().lock(), |event| println!("{}", event))
+                .expect("stdin should be readable");
+            return;
+        }
+        _ => {}
+    }
+    let datastream = Datastream::new(input);
+    match (datastream.start_of_packet(), datastream.start_of_message()) {
+        (Ok(start_index), Ok(start_of_message)) => println!(
+            "The datastream starts at index {} and the message at {}!",
+            start_index, start_of_message
+        ),
+        (Err(error), _) | (_, Err(error)) => println!("The datastream has {}", error),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,40 +214,76 @@ mod tests {
     #[test]
     fn test_seeking_packet() {
         assert_eq!(
-            Datastream::new("bvwbjplbgvbhsrlpgdmjqwftvncz").start_of_packet,
-            5
+            Datastream::new("bvwbjplbgvbhsrlpgdmjqwftvncz").start_of_packet(),
+            Ok(5)
         );
         assert_eq!(
-            Datastream::new("nppdvjthqldpwncqszvftbrmjlhg").start_of_packet,
-            6
+            Datastream::new("nppdvjthqldpwncqszvftbrmjlhg").start_of_packet(),
+            Ok(6)
         );
         assert_eq!(
-            Datastream::new("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").start_of_packet,
-            10
+            Datastream::new("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").start_of_packet(),
+            Ok(10)
         );
         assert_eq!(
-            Datastream::new("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").start_of_packet,
-            11
+            Datastream::new("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw").start_of_packet(),
+            Ok(11)
         );
     }
 
     #[test]
     fn test_seeking_message() {
         assert_eq!(
-            Datastream::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb").start_of_message,
-            19
+            Datastream::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb").start_of_message(),
+            Ok(19)
         );
         assert_eq!(
-            Datastream::new("bvwbjplbgvbhsrlpgdmjqwftvncz").start_of_message,
-            23
+            Datastream::new("bvwbjplbgvbhsrlpgdmjqwftvncz").start_of_message(),
+            Ok(23)
         );
         assert_eq!(
-            Datastream::new("nppdvjthqldpwncqszvftbrmjlhg").start_of_message,
-            23
+            Datastream::new("nppdvjthqldpwncqszvftbrmjlhg").start_of_message(),
+            Ok(23)
+        );
+        assert_eq!(
+            Datastream::new("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").start_of_message(),
+            Ok(29)
+        );
+    }
+
+    #[test]
+    fn should_match_set_based_scan() {
+        let input = "abcabdabcdeabcdefgaaaaaaaaabcdefghijklmnopqrstuvwxyz";
+        for window in 1..=27 {
+            assert_eq!(
+                find_marker(input.as_bytes(), window),
+                find_marker_with_sets(input, window),
+                "window {}",
+                window
+            );
+        }
+        assert_eq!(find_marker(input.as_bytes(), 0), Some(0));
+    }
+
+    #[test]
+    fn should_report_missing_markers() {
+        let owned = Datastream::new(b"abcabcabcd".to_vec());
+        let borrowed = Datastream::new("abcabcabcd");
+
+        assert_eq!(owned.start_of_packet(), Ok(10));
+        assert_eq!(
+            borrowed.start_of_message(),
+            Err(NoMarker {
+                window: 14,
+                searched: 10
+            })
         );
         assert_eq!(
-            Datastream::new("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg").start_of_message,
-            29
+            Datastream::new(String::new()).start_of_packet(),
+            Err(NoMarker {
+                window: 4,
+                searched: 0
+            })
         );
     }
 }
@@ -0,0 +1,63 @@
+/// Finds the same marker as [`crate::find_marker`] on a byte slice in memory. Each candidate window
+/// is checked from its end backwards, toggling the bit of every byte in a 256-bit mask with XOR.
+/// A bit which is cleared by the toggle is a duplicate, and no window starting at or before it can
+/// be a marker, so the next candidate starts right after it. On random data most windows are left
+/// after a few bytes, and whole windows are skipped at once.
+pub fn find_marker(input: &[u8], window: usize) -> Option<usize> {
+    if window == 0 {
+        return Some(0);
+    }
+    let mut start = 0;
+    'candidates: while start + window <= input.len() {
+        let mut mask = [0u64; 4];
+        for position in (start..start + window).rev() {
+            let byte = input[position] as usize;
+            let bit = 1 << (byte % 64);
+            mask[byte / 64] ^= bit;
+            if mask[byte / 64] & bit == 0 {
+                start = position + 1;
+                continue 'candidates;
+            }
+        }
+        return Some(start + window);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::find_marker_with_sets;
+
+    #[test]
+    fn should_match_window_scan_on_every_short_stream() {
+        let alphabet = b"abcd";
+        for length in 0..=8u32 {
+            for number in 0..alphabet.len().pow(length) {
+                let stream = (0..length)
+                    .map(|digit| alphabet[number / alphabet.len().pow(digit) % alphabet.len()])
+                    .collect::<Vec<_>>();
+                let text = std::str::from_utf8(&stream).unwrap();
+                for window in 1..=alphabet.len() + 1 {
+                    assert_eq!(
+                        find_marker(&stream, window),
+                        find_marker_with_sets(text, window),
+                        "{} with window {}",
+                        text,
+                        window
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn should_handle_every_byte_value() {
+        let stream = (0..=255u8).chain(0..=255).collect::<Vec<_>>();
+
+        assert_eq!(find_marker(&stream, 256), Some(256));
+        assert_eq!(find_marker(&stream[1..], 256), Some(256));
+        assert_eq!(find_marker(&stream, 257), None);
+        assert_eq!(find_marker(&stream, 0), Some(0));
+    }
+}
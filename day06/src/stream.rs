@@ -0,0 +1,167 @@
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+
+const CHUNK_SIZE: usize = 4096;
+
+/// Finds the first point where the last `window` bytes are all different, fed one byte at a time.
+/// Remembers where each byte was seen last, so the window start only ever moves forward.
+pub struct MarkerDetector {
+    window: usize,
+    /// 1-based absolute positions, 0 means not seen yet
+    last_seen: [usize; 256],
+    window_start: usize,
+    read: usize,
+    found: bool,
+}
+
+impl MarkerDetector {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window,
+            last_seen: [0; 256],
+            window_start: 0,
+            read: 0,
+            found: false,
+        }
+    }
+
+    /// Returns the number of bytes read so far when this byte completes the first marker.
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        if self.found {
+            return None;
+        }
+        self.window_start = self.window_start.max(self.last_seen[byte as usize]);
+        self.read += 1;
+        self.last_seen[byte as usize] = self.read;
+        self.found = self.read - self.window_start == self.window;
+        self.found.then_some(self.read)
+    }
+
+    pub fn is_found(&self) -> bool {
+        self.found
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Marker {
+    StartOfPacket,
+    StartOfMessage,
+}
+
+/// A marker with the number of bytes read from the start of the stream up to its end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Event {
+    pub marker: Marker,
+    pub offset: usize,
+}
+
+impl Display for Event {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let marker = match self.marker {
+            Marker::StartOfPacket => "start-of-packet",
+            Marker::StartOfMessage => "start-of-message",
+        };
+        write!(f, "{} marker after {} bytes", marker, self.offset)
+    }
+}
+
+/// Looks for both markers at once in a stream which does not need to be in memory.
+pub struct StreamDetector {
+    packet: MarkerDetector,
+    message: MarkerDetector,
+}
+
+impl StreamDetector {
+    pub fn new() -> Self {
+        Self {
+            packet: MarkerDetector::new(crate::PACKET_MARKER),
+            message: MarkerDetector::new(crate::MESSAGE_MARKER),
+        }
+    }
+
+    /// Calls `on_event` for every marker completed by the chunk, in stream order.
+    pub fn feed(&mut self, chunk: &[u8], mut on_event: impl FnMut(Event)) {
+        for byte in chunk {
+            if let Some(offset) = self.packet.push(*byte) {
+                on_event(Event {
+                    marker: Marker::StartOfPacket,
+                    offset,
+                });
+            }
+            if let Some(offset) = self.message.push(*byte) {
+                on_event(Event {
+                    marker: Marker::StartOfMessage,
+                    offset,
+                });
+            }
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.packet.is_found() && self.message.is_found()
+    }
+
+    /// Feeds the detector from `reader` until both markers are found or the stream ends.
+    pub fn read_from(
+        &mut self,
+        mut reader: impl Read,
+        mut on_event: impl FnMut(Event),
+    ) -> std::io::Result<()> {
+        let mut buffer = [0; CHUNK_SIZE];
+        while !self.is_done() {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            self.feed(&buffer[..read], &mut on_event);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out the input in pieces of at most `size` bytes like a slow pipe.
+    struct Trickle<'a> {
+        input: &'a [u8],
+        size: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.size.min(buf.len()).min(self.input.len());
+            buf[..read].copy_from_slice(&self.input[..read]);
+            self.input = &self.input[read..];
+            Ok(read)
+        }
+    }
+
+    #[test]
+    fn should_emit_events_with_absolute_offsets() {
+        let input = b"mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        for size in [1, 3, 30] {
+            let mut events = vec![];
+            StreamDetector::new()
+                .read_from(Trickle { input, size }, |event| events.push(event))
+                .unwrap();
+
+            assert_eq!(
+                events,
+                vec![
+                    Event {
+                        marker: Marker::StartOfPacket,
+                        offset: 7
+                    },
+                    Event {
+                        marker: Marker::StartOfMessage,
+                        offset: 19
+                    }
+                ]
+            );
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use regex::Regex;
+use std::fmt::{Display, Formatter};
 use std::iter::Sum;
 use std::ops::{Add, Sub};
 
@@ -52,10 +53,87 @@ struct Node {
     value: NodeEntry,
 }
 
+/// What to do when a directory is listed again and a file shows up with another size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SizeMismatch {
+    /// Reports a [`SizeMismatchWarning`] and takes the size of the latest listing.
+    Warn,
+    /// Stops with [`FileSystemError::SizeChanged`].
+    Error,
+}
+
+#[derive(Debug, PartialEq)]
+enum FileSystemError {
+    SizeChanged {
+        name: String,
+        listed: FileSize,
+        relisted: FileSize,
+    },
+    /// An entry was listed as a file and as a folder in the same folder.
+    KindChanged(String),
+    NotFound(String),
+    NotAFolder(String),
+}
+
+impl Display for FileSystemError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileSystemError::SizeChanged {
+                name,
+                listed,
+                relisted,
+            } => write!(
+                f,
+                "file {} was listed with size {} and now with {}",
+                name, listed.0, relisted.0
+            ),
+            FileSystemError::KindChanged(name) => {
+                write!(f, "{} was listed as a file and as a folder", name)
+            }
+            FileSystemError::NotFound(path) => write!(f, "there is no {}", path),
+            FileSystemError::NotAFolder(path) => write!(f, "{} is a file, not a folder", path),
+        }
+    }
+}
+
+/// A failed line of the transcript, 1-based.
+#[derive(Debug, PartialEq)]
+struct ExecutionError {
+    line: usize,
+    error: FileSystemError,
+}
+
+impl Display for ExecutionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+/// A file which was listed again with another size on a line of the transcript, 1-based, and now
+/// has the size of that listing.
+#[derive(Debug, PartialEq)]
+struct SizeMismatchWarning {
+    line: usize,
+    name: String,
+    listed: FileSize,
+    relisted: FileSize,
+}
+
+impl Display for SizeMismatchWarning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}: file {} was listed with size {} and now with {}",
+            self.line, self.name, self.listed.0, self.relisted.0
+        )
+    }
+}
+
 #[derive(Debug)]
 struct FileSystem {
     contents: Vec<Node>,
     active_folder: ID,
+    size_mismatch: SizeMismatch,
 }
 
 impl FileSystem {
@@ -72,57 +150,142 @@ impl FileSystem {
                 }),
             }],
             active_folder: id,
+            size_mismatch: SizeMismatch::Warn,
         }
     }
 
-    fn move_into_folder(&mut self, search_name: &str) {
-        if search_name == ".." {
-            self.active_folder = self
-                .get_parent_folder(self.active_folder)
-                .map(|n| n.id)
-                .unwrap_or(ID(0));
-
-            return;
+    fn with_size_mismatch(size_mismatch: SizeMismatch) -> Self {
+        Self {
+            size_mismatch,
+            ..Self::new()
         }
-        if search_name == "/" {
-            self.active_folder = ID(0);
-            return;
+    }
+
+    /// Changes the active folder to an absolute path like `/a/b` or a path relative to the active
+    /// folder like `../x`. `..` stays at the root when there is no parent.
+    fn move_into_folder(&mut self, path: &str) -> Result<(), FileSystemError> {
+        let id = self.resolve(path)?;
+        match &self.contents[id.0].value {
+            NodeEntry::Folder(_) => {
+                self.active_folder = id;
+                Ok(())
+            }
+            NodeEntry::File(_) => Err(FileSystemError::NotAFolder(self.path_of(id))),
         }
-        let Some(node) = self.contents.get(self.active_folder.0) else { return };
-        match &node.value {
-            NodeEntry::File { .. } => {
-                unreachable!("active folder is a file");
+    }
+
+    /// Finds the node of an absolute or relative path, every segment but the last has to be a
+    /// folder.
+    fn resolve(&self, path: &str) -> Result<ID, FileSystemError> {
+        let mut id = if path.starts_with('/') {
+            ID(0)
+        } else {
+            self.active_folder
+        };
+        for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+            if let NodeEntry::File(_) = self.contents[id.0].value {
+                return Err(FileSystemError::NotAFolder(self.path_of(id)));
             }
-            NodeEntry::Folder(Folder { entries, .. }) => {
-                for id in entries {
-                    let Some(node) = self.contents.get(id.0) else { continue; };
-                    match &node.value {
-                        NodeEntry::File { .. } => continue,
-                        NodeEntry::Folder(Folder { name, .. }) => {
-                            if name == search_name {
-                                self.active_folder = id.to_owned();
-                                return;
-                            }
-                        }
-                    }
+            id = match segment {
+                "." => id,
+                ".." => self.contents[id.0].parent.unwrap_or(ID(0)),
+                name => {
+                    self.find_entry(id, name)
+                        .ok_or_else(|| {
+                            let parent = self.path_of(id);
+                            let separator = if parent.ends_with('/') { "" } else { "/" };
+                            FileSystemError::NotFound(format!("{}{}{}", parent, separator, name))
+                        })?
+                        .id
                 }
-            }
+            };
         }
+        Ok(id)
+    }
+
+    /// The absolute path of a node, `/` for the root.
+    fn path_of(&self, id: ID) -> String {
+        let mut names = vec![];
+        let mut current = &self.contents[id.0];
+        while let Some(parent) = current.parent {
+            let (NodeEntry::File(File { name, .. }) | NodeEntry::Folder(Folder { name, .. })) =
+                &current.value;
+            names.push(name.as_str());
+            current = &self.contents[parent.0];
+        }
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    /// Looks for an entry of a folder by name, so that listing a folder again adds nothing twice.
+    fn find_entry(&self, folder: ID, search_name: &str) -> Option<&Node> {
+        let NodeEntry::Folder(Folder { entries, .. }) = &self.contents[folder.0].value else {
+            panic!("entries can only be searched in folders")
+        };
+        entries.iter().map(|id| &self.contents[id.0]).find(|node| {
+            let (NodeEntry::File(File { name, .. }) | NodeEntry::Folder(Folder { name, .. })) =
+                &node.value;
+            name == search_name
+        })
     }
 
-    fn add_folder(&mut self, name: &str) {
+    fn add_folder(&mut self, name: &str) -> Result<(), FileSystemError> {
+        match self
+            .find_entry(self.active_folder, name)
+            .map(|node| &node.value)
+        {
+            Some(NodeEntry::Folder(_)) => return Ok(()),
+            Some(NodeEntry::File(_)) => return Err(FileSystemError::KindChanged(name.to_string())),
+            None => {}
+        }
         self.add_node_entry(NodeEntry::Folder(Folder {
             entries: vec![],
             name: name.to_string(),
             size: FileSize(0),
         }));
+        Ok(())
     }
 
-    fn add_file(&mut self, name: &str, size: FileSize) {
-        let id = self.add_node_entry(NodeEntry::File(File {
-            size,
-            name: name.to_string(),
-        }));
+    /// Returns the size the file was listed with before if that was another one and mismatches
+    /// only warn.
+    fn add_file(
+        &mut self,
+        name: &str,
+        size: FileSize,
+    ) -> Result<Option<FileSize>, FileSystemError> {
+        match self
+            .find_entry(self.active_folder, name)
+            .map(|node| (node.id, &node.value))
+        {
+            Some((_, NodeEntry::File(file))) if file.size == size => Ok(None),
+            Some((id, NodeEntry::File(file))) => {
+                let listed = file.size;
+                if self.size_mismatch == SizeMismatch::Error {
+                    return Err(FileSystemError::SizeChanged {
+                        name: name.to_string(),
+                        listed,
+                        relisted: size,
+                    });
+                }
+                if let NodeEntry::File(file) = &mut self.contents[id.0].value {
+                    file.size = size;
+                }
+                self.update_folder_sizes(id, |folder_size| folder_size - listed + size);
+                Ok(Some(listed))
+            }
+            Some((_, NodeEntry::Folder(_))) => Err(FileSystemError::KindChanged(name.to_string())),
+            None => {
+                let id = self.add_node_entry(NodeEntry::File(File {
+                    size,
+                    name: name.to_string(),
+                }));
+                self.update_folder_sizes(id, |folder_size| size + folder_size);
+                Ok(None)
+            }
+        }
+    }
+
+    fn update_folder_sizes(&mut self, id: ID, update: impl Fn(FileSize) -> FileSize) {
         let mut current_id = id;
 
         while let Some(next_node) = self.get_parent_folder(current_id) {
@@ -131,13 +294,13 @@ impl FileSystem {
                 size: folder_size, ..
             }) = &mut next_node.value
             {
-                *folder_size = size + *folder_size;
+                *folder_size = update(*folder_size);
             }
         }
     }
 
     fn get_parent_folder(&mut self, parent_of: ID) -> Option<&mut Node> {
-        let Some(parent_id) = self.contents[parent_of.0].parent else { return None };
+        let parent_id = self.contents[parent_of.0].parent?;
         self.contents.get_mut(parent_id.0)
     }
 
@@ -185,19 +348,41 @@ impl FileSystem {
         self.print_inner(&vec![ID(0)], 0);
     }
 
-    fn execute_instructions(&mut self, input: &str) {
+    /// Replays the transcript and returns the size mismatches which were only warned about, with
+    /// [`SizeMismatch::Error`] they stop the replay as [`FileSystemError::SizeChanged`] instead.
+    fn execute_instructions(
+        &mut self,
+        input: &str,
+    ) -> Result<Vec<SizeMismatchWarning>, ExecutionError> {
         let cd = Regex::new(r"\$ cd (.+)").unwrap();
         let dir = Regex::new(r"dir (.+)").unwrap();
         let file = Regex::new(r"(\d+) (.+)").unwrap();
-        input.lines().for_each(|line| {
-            if let Some(cd) = cd.captures(line) {
-                self.move_into_folder(&cd[1])
+        let mut warnings = vec![];
+        for (index, line) in input.lines().enumerate() {
+            let result = if let Some(cd) = cd.captures(line) {
+                self.move_into_folder(&cd[1]).map(|_| None)
             } else if let Some(dir) = dir.captures(line) {
-                self.add_folder(&dir[1])
+                self.add_folder(&dir[1]).map(|_| None)
             } else if let Some(file) = file.captures(line) {
-                self.add_file(&file[2], FileSize(*&file[1].parse::<usize>().unwrap()))
-            }
-        });
+                let size = FileSize(file[1].parse::<usize>().unwrap());
+                self.add_file(&file[2], size).map(|listed| {
+                    listed.map(|listed| SizeMismatchWarning {
+                        line: index + 1,
+                        name: file[2].to_string(),
+                        listed,
+                        relisted: size,
+                    })
+                })
+            } else {
+                Ok(None)
+            };
+            let warning = result.map_err(|error| ExecutionError {
+                line: index + 1,
+                error,
+            })?;
+            warnings.extend(warning);
+        }
+        Ok(warnings)
     }
 
     fn sum_folders_with_size_less_than(&self, max_size: usize) -> FileSize {
@@ -241,9 +426,37 @@ impl FileSystem {
 
 fn main() {
     let input = include_str!("input.txt");
-    let mut system = FileSystem::new();
-
-    system.execute_instructions(input);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let size_mismatch = if args.iter().any(|arg| arg == "--strict") {
+        SizeMismatch::Error
+    } else {
+        SizeMismatch::Warn
+    };
+    let mut system = FileSystem::with_size_mismatch(size_mismatch);
+
+    match system.execute_instructions(input) {
+        Ok(warnings) => warnings
+            .iter()
+            .for_each(|warning| eprintln!("Warning: {}", warning)),
+        Err(error) => {
+            println!("The transcript cannot be replayed, {}", error);
+            return;
+        }
+    }
+    match (args.first().map(String::as_str), args.get(1)) {
+        (Some("tree"), _) => return system.print(),
+        (Some("ls"), Some(path)) => {
+            match system.resolve(path) {
+                Ok(id) => {
+                    println!("{}", system.path_of(id));
+                    system.print_inner(&vec![id], 1);
+                }
+                Err(error) => println!("Cannot list {}: {}", path, error),
+            }
+            return;
+        }
+        _ => {}
+    }
 
     let sum = system.sum_folders_with_size_less_than(100000);
 
@@ -267,18 +480,18 @@ mod tests {
     #[test]
     fn test_folder_gen() {
         let mut system = FileSystem::new();
-        system.add_file("foo", FileSize(10));
-        system.add_folder("baz");
-        system.add_folder("buzz");
-        system.move_into_folder("buzz");
-        system.add_folder("zubb");
-        system.move_into_folder("zubb");
-        system.add_file("innerfoo", FileSize(10));
-        system.add_file("innerbar", FileSize(20));
-        system.move_into_folder("..");
-        system.add_file("bar", FileSize(20));
-        system.move_into_folder("/");
-        system.add_file("bary", FileSize(20));
+        system.add_file("foo", FileSize(10)).unwrap();
+        system.add_folder("baz").unwrap();
+        system.add_folder("buzz").unwrap();
+        system.move_into_folder("buzz").unwrap();
+        system.add_folder("zubb").unwrap();
+        system.move_into_folder("zubb").unwrap();
+        system.add_file("innerfoo", FileSize(10)).unwrap();
+        system.add_file("innerbar", FileSize(20)).unwrap();
+        system.move_into_folder("..").unwrap();
+        system.add_file("bar", FileSize(20)).unwrap();
+        system.move_into_folder("/").unwrap();
+        system.add_file("bary", FileSize(20)).unwrap();
 
         system.print()
     }
@@ -310,7 +523,7 @@ $ ls
 8033020 d.log
 5626152 d.ext
 7214296 k";
-        system.execute_instructions(input);
+        system.execute_instructions(input).unwrap();
         let size = system.sum_folders_with_size_less_than(100000);
         let folder =
             system.get_smallest_directory_to_delete(FileSize(70000000), FileSize(30000000));
@@ -320,4 +533,91 @@ $ ls
 
         system.print()
     }
+
+    #[test]
+    fn should_ignore_repeated_listings() {
+        let input = "$ cd /\n$ ls\ndir a\n10 b\n$ cd a\n$ ls\n5 c\n$ cd /\n$ ls\ndir a\n10 b";
+        let mut system = FileSystem::new();
+        system.execute_instructions(input).unwrap();
+
+        assert_eq!(system.contents.len(), 4);
+        assert_eq!(system.sum_folders_with_size_less_than(100), FileSize(20));
+    }
+
+    #[test]
+    fn should_handle_changed_sizes() {
+        let input = "$ ls\ndir a\n$ cd a\n$ ls\n10 b\n$ ls\n15 b";
+        let mut strict = FileSystem::with_size_mismatch(SizeMismatch::Error);
+        let mut lenient = FileSystem::new();
+
+        assert_eq!(
+            strict.execute_instructions(input),
+            Err(ExecutionError {
+                line: 7,
+                error: FileSystemError::SizeChanged {
+                    name: "b".to_string(),
+                    listed: FileSize(10),
+                    relisted: FileSize(15)
+                }
+            })
+        );
+        assert_eq!(
+            lenient.execute_instructions(input),
+            Ok(vec![SizeMismatchWarning {
+                line: 7,
+                name: "b".to_string(),
+                listed: FileSize(10),
+                relisted: FileSize(15)
+            }])
+        );
+        assert_eq!(lenient.sum_folders_with_size_less_than(100), FileSize(30));
+        assert_eq!(
+            lenient.execute_instructions("$ cd /\n$ ls\n1 a"),
+            Err(ExecutionError {
+                line: 3,
+                error: FileSystemError::KindChanged("a".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn should_resolve_paths() {
+        let mut system = FileSystem::new();
+        system
+            .execute_instructions("$ ls\ndir a\n$ cd a\n$ ls\ndir b\n1 c\n$ cd b\n$ ls\n2 d")
+            .unwrap();
+
+        let b = system.resolve("/a/b").unwrap();
+        assert_eq!(system.path_of(b), "/a/b");
+        assert_eq!(
+            system.resolve("../c").map(|id| system.path_of(id)),
+            Ok("/a/c".to_string())
+        );
+        assert_eq!(
+            system.resolve("../../..").map(|id| system.path_of(id)),
+            Ok("/".to_string())
+        );
+        assert_eq!(
+            system.resolve("/a/x"),
+            Err(FileSystemError::NotFound("/a/x".to_string()))
+        );
+        assert_eq!(
+            system.resolve("/x"),
+            Err(FileSystemError::NotFound("/x".to_string()))
+        );
+        assert_eq!(
+            system.resolve("/a/c/d"),
+            Err(FileSystemError::NotAFolder("/a/c".to_string()))
+        );
+        assert_eq!(
+            system.resolve("/a/c/.."),
+            Err(FileSystemError::NotAFolder("/a/c".to_string()))
+        );
+        assert_eq!(
+            system.move_into_folder("../c"),
+            Err(FileSystemError::NotAFolder("/a/c".to_string()))
+        );
+        system.move_into_folder("/a/./b/../b").unwrap();
+        assert_eq!(system.active_folder, b);
+    }
 }